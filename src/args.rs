@@ -1,14 +1,26 @@
-use clap::{Parser, ValueEnum};
-use ratatui::style::Color;
+use std::{path::PathBuf, time::Duration};
+
+use clap::{
+  builder::{PossibleValue, PossibleValuesParser},
+  CommandFactory, FromArgMatches, Parser, ValueEnum,
+};
+use ratatui::{style::Color, widgets::Padding};
+use tui_rain_cli::{
+  BackdropDrops, Capabilities, ColorSupport, Distribution, EffectConfig, EffectRegistry, FallDirection, GraphicsProtocol, MaskMode, MessageAlignment,
+  MessageBackdrop, MessageEdge, MessagePosition, RainDensity, RainSpeed, Resolution, Variation,
+};
+
+/// The shortest interval at which the message command may be rerun.
+const MIN_MESSAGE_INTERVAL: Duration = Duration::from_millis(100);
 
 /// CLI wrapper around tui-rs to create terminal rain effects.
 /// Added message functionality for a more festive touch.
-#[derive(Parser, Debug)]
+#[derive(Parser, Clone, Debug)]
 #[clap(author, version, about)]
 pub struct Args {
-  /// Type of rain effect [rain|matrix|snow|data|emoji]
-  #[clap(short='t', long, default_value_t = RainType::Snow, value_enum)]
-  pub rain_type: RainType,
+  /// Type of rain effect
+  #[clap(short = 't', long, default_value = "snow")]
+  pub rain_type: String,
 
   /// Rain density computes the number of drops based on the frame size. Lower value is denser.
   #[clap(short, long)]
@@ -22,10 +34,30 @@ pub struct Args {
   #[clap(short = 'S', long)]
   pub variance_speed: Option<f64>,
 
+  /// Distribution of the speed of drops, spread by the speed variance [uniform|normal|log-normal|bimodal]
+  #[clap(long)]
+  pub speed_distribution: Option<Distribution>,
+
   /// Tail lifespan in milliseconds
   #[clap(short, long)]
   pub lifespan_tail: Option<u64>,
 
+  /// How the tail lifespan varies per drop [<uniform|normal|log-normal|bimodal>[:<variance>]]
+  #[clap(long)]
+  pub tail_variation: Option<Variation>,
+
+  /// How the brightness varies per drop [<uniform|normal|log-normal|bimodal>[:<variance>]]
+  #[clap(long)]
+  pub brightness_variation: Option<Variation>,
+
+  /// How the head color varies per drop, towards the rain color [<uniform|normal|log-normal|bimodal>[:<variance>]]
+  #[clap(long)]
+  pub head_color_variation: Option<Variation>,
+
+  /// Make slow drops shorter and dimmer, as if they were further away
+  #[clap(long, default_value_t = false)]
+  pub depth: bool,
+
   /// Color of the rain [black|red|green|yellow|blue|magenta|cyan|gray|darkgray|lightred|lightgreen|lightyellow|lightblue|lightmagenta|lightcyan|white]
   #[clap(short, long)]
   pub color: Option<Color>,
@@ -38,9 +70,25 @@ pub struct Args {
   #[clap(short, long)]
   pub effect_dim: Option<bool>,
 
-  /// Message to display
+  /// Direction the drops fall in, defaults to the rain type's [down|up|left|right|<degrees clockwise from down>]
+  #[clap(long, allow_hyphen_values = true)]
+  pub direction: Option<FallDirection>,
+
+  /// Gravity in pixels / second², making drops accelerate from rest up to their speed
+  #[clap(long)]
+  pub gravity: Option<f64>,
+
+  /// How finely drops are placed within cells, drawing them as dots at finer resolutions, defaults to the rain type's [cell|half-block|braille]
+  #[clap(long)]
+  pub resolution: Option<Resolution>,
+
+  /// Lock drops to columns this many cells apart, with at most one stream per column, like the classic matrix look
+  #[clap(long)]
+  pub columns: Option<u16>,
+
+  /// Message to display, with placeholders [{time[:fmt]}|{date[:fmt]}|{hostname}|{user}|{uptime}|{countdown:YYYY-MM-DDTHH:MM}|{fps}] and style tags like `[b]bold[/b]` or `[red on blue]text[/]`. Repeat to cycle through several messages
   #[clap(short, long)]
-  pub message: Option<String>,
+  pub message: Vec<String>,
 
   /// Color of the message [black|red|green|yellow|blue|magenta|cyan|gray|darkgray|lightred|lightgreen|lightyellow|lightblue|lightmagenta|lightcyan|white]
   #[clap(short = 'n', long)]
@@ -50,89 +98,303 @@ pub struct Args {
   #[clap(short = 'o', long)]
   pub message_speed: Option<f64>,
 
+  /// How a message appears [drop-in|static|blink|decode]
+  #[clap(long, default_value_t = MessageEffect::DropIn, value_enum)]
+  pub message_effect: MessageEffect,
+
+  /// Longest time the decode effect takes before revealing what is left [e.g. 500ms|10s|5m|1h]
+  #[clap(long, default_value = "20s", value_parser = parse_duration)]
+  pub decode_timeout: Duration,
+
+  /// Where the message is placed [center|top|bottom|left|right|top-left|top-right|bottom-left|bottom-right|<x>%,<y>%]
+  #[clap(long, default_value = "center")]
+  pub message_position: MessagePosition,
+
+  /// Alignment of the lines of a multi-line message [left|center|right]
+  #[clap(long, default_value = "center")]
+  pub message_align: MessageAlignment,
+
+  /// Space kept free around the message [<all>|<vertical>,<horizontal>|<top>,<right>,<bottom>,<left>]
+  #[clap(long, default_value = "0,1", value_parser = parse_padding)]
+  pub message_padding: Padding,
+
+  /// Screen edge the message drops in from [top|bottom|left|right]
+  #[clap(long, default_value = "top")]
+  pub message_edge: MessageEdge,
+
+  /// Backdrop behind the message to keep it readable [none|clear|dim|solid:<color>|border[:plain|rounded|double|thick]]
+  #[clap(long, default_value = "none")]
+  pub message_backdrop: MessageBackdrop,
+
+  /// Space the backdrop extends beyond the message [<all>|<vertical>,<horizontal>|<top>,<right>,<bottom>,<left>]
+  #[clap(long, default_value = "0,1", value_parser = parse_padding)]
+  pub backdrop_margin: Padding,
+
+  /// Whether drops pass behind the backdrop or stop at it [pass-behind|stop]
+  #[clap(long, default_value = "pass-behind")]
+  pub backdrop_drops: BackdropDrops,
+
+  /// How long each message is shown when cycling through several [e.g. 500ms|10s|5m|1h]
+  #[clap(long, default_value = "10s", value_parser = parse_duration)]
+  pub message_duration: Duration,
+
+  /// Playlist file of messages separated by header lines like `--- 10s color=yellow speed=3 effect=blink`
+  #[clap(long, conflicts_with_all = ["message", "message_file", "message_cmd"])]
+  pub message_playlist: Option<PathBuf>,
+
+  /// Scene file of steps like `rain 20s fade=3s speed=4 message="Welcome"`, looped with transitions between them
+  #[clap(long, conflicts_with_all = ["message", "message_file", "message_cmd", "message_playlist"])]
+  pub scene: Option<PathBuf>,
+
+  /// File to read the message from, reloaded whenever it changes
+  #[clap(long, conflicts_with_all = ["message", "message_cmd"])]
+  pub message_file: Option<PathBuf>,
+
+  /// Shell command whose output is displayed as the message, e.g. `fortune -s`
+  #[clap(long, conflicts_with = "message")]
+  pub message_cmd: Option<String>,
+
+  /// How often to rerun the message command, at least 100ms [e.g. 500ms|10s|5m|1h]
+  #[clap(long, default_value = "10s", value_parser = parse_interval)]
+  pub message_interval: Duration,
+
+  /// Message to display when the message file or command fails
+  #[clap(long)]
+  pub message_fallback: Option<String>,
+
+  /// Mask that shapes or colors the rain, as a PNG or PPM/PGM image or an ASCII-art text file
+  #[clap(long)]
+  pub mask: Option<PathBuf>,
+
+  /// How the mask affects the rain [clip|cutout|tint]
+  #[clap(long, default_value = "tint")]
+  pub mask_mode: MaskMode,
+
+  /// Color of ASCII-art masks, defaults to the head color [black|red|green|yellow|blue|magenta|cyan|gray|darkgray|lightred|lightgreen|lightyellow|lightblue|lightmagenta|lightcyan|white]
+  #[clap(long)]
+  pub mask_color: Option<Color>,
+
+  /// Read the drop glyphs from stdin in arrival order, e.g. `tail -f app.log | tui-rain-cli -t data --stdin`
+  #[clap(short = 'i', long, default_value_t = false)]
+  pub stdin: bool,
+
+  /// What the terminal can show, detected by default, as limitations like `256,ascii,no-dim` or `full` [truecolor|256|16|mono|ascii|no-dim|full]
+  #[clap(long)]
+  pub compat: Option<Capabilities>,
+
+  /// Render without colors, showing heads and depth with glyphs, bold, dim and reverse video instead. Also enabled by setting `NO_COLOR`
+  #[clap(long, default_value_t = false)]
+  pub no_color: bool,
+
+  /// Draw drops in pixels with a terminal graphics protocol, in cells if the terminal doesn't support it
+  #[clap(long, value_enum, default_value = "off")]
+  pub graphics: Graphics,
+
   /// Verbose mode
   #[clap(short, long, default_value_t = false)]
   pub verbose: bool,
 }
 
 impl Args {
-  pub fn sanitize(&mut self) {
-    match self.rain_type {
-      RainType::Rain => {
-        self.density.get_or_insert(30);
-        self.speed.get_or_insert(2.0);
-        self.variance_speed.get_or_insert(10.0);
-        self.lifespan_tail.get_or_insert(500);
-        self.color.get_or_insert(Color::LightBlue);
-        self.head_color.get_or_insert(Color::White);
-        self.effect_dim.get_or_insert(true);
-        self.message_color.get_or_insert(Color::Blue);
-        self.message_speed.get_or_insert(2.0);
-        self.message.get_or_insert(String::from(""));
-      }
-      RainType::Matrix => {
-        self.density.get_or_insert(50);
-        self.speed.get_or_insert(5.0);
-        self.variance_speed.get_or_insert(0.5);
-        self.lifespan_tail.get_or_insert(3000);
-        self.color.get_or_insert(Color::LightGreen);
-        self.head_color.get_or_insert(Color::White);
-        self.effect_dim.get_or_insert(true);
-        self.message_color.get_or_insert(Color::Green);
-        self.message_speed.get_or_insert(2.0);
-        self.message.get_or_insert(String::from(""));
-      }
-      RainType::Snow => {
-        self.density.get_or_insert(30);
-        self.speed.get_or_insert(2.0);
-        self.variance_speed.get_or_insert(0.3);
-        self.lifespan_tail.get_or_insert(500);
-        self.color.get_or_insert(Color::White);
-        self.head_color.get_or_insert(Color::White);
-        self.effect_dim.get_or_insert(true);
-        self.message_color.get_or_insert(Color::Gray);
-        self.message_speed.get_or_insert(2.0);
-        self.message.get_or_insert(String::from(""));
-      }
-      RainType::Data => {
-        self.density.get_or_insert(70);
-        self.speed.get_or_insert(2.0);
-        self.variance_speed.get_or_insert(3.0);
-        self.lifespan_tail.get_or_insert(1000);
-        self.color.get_or_insert(Color::LightBlue);
-        self.head_color.get_or_insert(Color::White);
-        self.effect_dim.get_or_insert(true);
-        self.message_color.get_or_insert(Color::Blue);
-        self.message_speed.get_or_insert(2.0);
-        self.message.get_or_insert(String::from(""));
-      }
-      RainType::Emoji => {
-        self.density.get_or_insert(20);
-        self.speed.get_or_insert(10.0);
-        self.variance_speed.get_or_insert(0.1);
-        self.lifespan_tail.get_or_insert(500);
-        self.color.get_or_insert(Color::White);
-        self.head_color.get_or_insert(Color::White);
-        self.effect_dim.get_or_insert(true);
-        self.message_color.get_or_insert(Color::Yellow);
-        self.message_speed.get_or_insert(2.0);
-        self.message.get_or_insert(String::from(""));
-      }
+  /// Parse the command line, accepting the names of the registered effects for
+  /// `--rain-type`. Exits with the usage on errors, like [`Parser::parse`].
+  pub fn parse_with(effects: &EffectRegistry) -> Args {
+    let matches = Args::command()
+      .mut_arg("rain_type", |arg| arg.value_parser(effect_parser(effects)))
+      .get_matches();
+    Args::from_arg_matches(&matches).unwrap_or_else(|error| error.exit())
+  }
+
+  /// Fill in the settings that weren't given with the defaults of the chosen effect.
+  pub fn sanitize(&mut self, effects: &EffectRegistry) {
+    let defaults = effects.defaults(&self.rain_type).cloned().unwrap_or_default();
+    self.density.get_or_insert(match defaults.density {
+      RainDensity::Relative { sparseness } => sparseness,
+      RainDensity::Dense => 20,
+      RainDensity::Sparse => 100,
+      RainDensity::Normal | RainDensity::Absolute { .. } => 50,
+    });
+    self.speed.get_or_insert(match defaults.speed {
+      RainSpeed::Absolute { speed } => speed,
+      RainSpeed::Fast => 20.0,
+      RainSpeed::Normal => 10.0,
+      RainSpeed::Slow => 5.0,
+    });
+    self.variance_speed.get_or_insert(defaults.speed_variance);
+    self.lifespan_tail.get_or_insert(defaults.tail_lifespan.as_millis() as u64);
+    self.color.get_or_insert(defaults.color);
+    self.head_color.get_or_insert(defaults.head_color);
+    self.effect_dim.get_or_insert(defaults.bold_dim_effect);
+    self.message_color.get_or_insert(defaults.message_color);
+    self.resolution.get_or_insert(defaults.resolution);
+    self.message_speed.get_or_insert(2.0);
+  }
+
+  /// Get the settings shared by all effects, once sanitized.
+  pub fn effect_config(&self) -> EffectConfig {
+    EffectConfig {
+      density: RainDensity::Relative {
+        sparseness: self.density.unwrap(),
+      },
+      speed: RainSpeed::Absolute { speed: self.speed.unwrap() },
+      speed_variance: self.variance_speed.unwrap(),
+      tail_lifespan: Duration::from_millis(self.lifespan_tail.unwrap()),
+      color: self.color.unwrap(),
+      head_color: self.head_color.unwrap(),
+      bold_dim_effect: self.effect_dim.unwrap(),
+      message_color: self.message_color.unwrap(),
+      resolution: self.resolution.unwrap(),
+      ..EffectConfig::default()
+    }
+  }
+
+  /// Get what the terminal can show: `--compat`, or what the environment suggests
+  /// without it. Colors are off with `--no-color` or `NO_COLOR`, even with `--compat`.
+  pub fn capabilities(&self, env: impl Fn(&str) -> Option<String>) -> Capabilities {
+    let mut capabilities = self.compat.unwrap_or_else(|| Capabilities::detect(&env));
+    if self.no_color || env("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+      capabilities.colors = ColorSupport::Monochrome;
+    }
+    capabilities
+  }
+}
+
+/// Get the effects selectable with `--rain-type`.
+///
+/// Effects shipped in other crates can be added here with [`EffectRegistry::with_effect`].
+pub fn effects() -> EffectRegistry {
+  EffectRegistry::builtin()
+}
+
+/// Build a parser accepting the names of the registered effects.
+fn effect_parser(effects: &EffectRegistry) -> PossibleValuesParser {
+  PossibleValuesParser::new(
+    effects
+      .names()
+      .map(|name| PossibleValue::new(name.to_string()).help(effects.about(name).unwrap_or_default().to_string())),
+  )
+}
+
+/// Parse a duration with an optional unit suffix [ms|s|m|h], defaulting to seconds.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+  let value = value.trim();
+  let split = value.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(value.len());
+  let (amount, unit) = value.split_at(split);
+  let amount: f64 = amount.parse().map_err(|_| format!("invalid duration `{value}`"))?;
+  let secs = match unit.trim() {
+    "ms" => amount / 1000.0,
+    "" | "s" => amount,
+    "m" => amount * 60.0,
+    "h" => amount * 3600.0,
+    unit => return Err(format!("unknown duration unit `{unit}`, expected one of ms, s, m, h")),
+  };
+  Duration::try_from_secs_f64(secs).map_err(|e| e.to_string())
+}
+
+/// Parse how often to rerun the message command. Shorter intervals would keep a core
+/// busy forking the command back to back.
+fn parse_interval(value: &str) -> Result<Duration, String> {
+  let interval = parse_duration(value)?;
+  if interval < MIN_MESSAGE_INTERVAL {
+    return Err(format!("interval `{value}` is too short, expected at least 100ms"));
+  }
+  Ok(interval)
+}
+
+/// Parse a padding given as one, two or four comma separated values, like CSS margins.
+fn parse_padding(value: &str) -> Result<Padding, String> {
+  let values = value
+    .split(',')
+    .map(|v| v.trim().parse::<u16>().map_err(|_| format!("invalid padding `{value}`")))
+    .collect::<Result<Vec<_>, _>>()?;
+  match values[..] {
+    [all] => Ok(Padding::uniform(all)),
+    [vertical, horizontal] => Ok(Padding::symmetric(horizontal, vertical)),
+    [top, right, bottom, left] => Ok(Padding::new(left, right, top, bottom)),
+    _ => Err(format!("invalid padding `{value}`, expected 1, 2 or 4 values")),
+  }
+}
+
+/// Enum for graphics protocols to draw drops with
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Graphics {
+  /// Draw drops in cells
+  Off,
+  /// Use the protocol the terminal seems to support, if any
+  Auto,
+  /// Sixel images
+  Sixel,
+  /// The kitty graphics protocol
+  Kitty,
+}
+
+impl Graphics {
+  /// Get the protocol to draw drops with, or `None` to draw them in cells.
+  pub fn protocol(self) -> Option<GraphicsProtocol> {
+    match self {
+      Graphics::Off => None,
+      Graphics::Auto => GraphicsProtocol::detect(|name| std::env::var(name).ok()),
+      Graphics::Sixel => Some(GraphicsProtocol::Sixel),
+      Graphics::Kitty => Some(GraphicsProtocol::Kitty),
     }
   }
 }
 
-/// Enum for rain effects
+/// Enum for message effects
 #[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
-pub enum RainType {
-  /// Rain effect
-  Rain,
-  /// Matrix effect
-  Matrix,
-  /// Snow effect
-  Snow,
-  /// Data effect
-  Data,
-  /// Emoji effect
-  Emoji,
+pub enum MessageEffect {
+  /// Drop in from the top
+  DropIn,
+  /// Appear in place
+  Static,
+  /// Appear in place and blink
+  Blink,
+  /// Appear in place as drops decode each character
+  Decode,
+}
+
+impl From<MessageEffect> for tui_rain_cli::MessageEffect {
+  fn from(effect: MessageEffect) -> Self {
+    match effect {
+      MessageEffect::DropIn => tui_rain_cli::MessageEffect::DropIn,
+      MessageEffect::Static => tui_rain_cli::MessageEffect::Static,
+      MessageEffect::Blink => tui_rain_cli::MessageEffect::Blink,
+      MessageEffect::Decode => tui_rain_cli::MessageEffect::Decode,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_duration_accepts_units() {
+    assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+    assert_eq!(parse_duration("1.5"), Ok(Duration::from_millis(1500)));
+    assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+    assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+    assert!(parse_duration("5d").is_err());
+    assert!(parse_duration("fast").is_err());
+  }
+
+  #[test]
+  fn parse_interval_rejects_intervals_under_100ms() {
+    assert!(parse_interval("0").is_err());
+    assert!(parse_interval("50ms").is_err());
+    assert_eq!(parse_interval("100ms"), Ok(Duration::from_millis(100)));
+    assert_eq!(parse_interval("10s"), Ok(Duration::from_secs(10)));
+  }
+
+  #[test]
+  fn no_color_applies_on_top_of_compat() {
+    let env = |no_color: &'static str| move |name: &str| (name == "NO_COLOR").then(|| no_color.to_string());
+    let args = Args::parse_from(["tui-rain-cli", "--compat", "256,ascii"]);
+    assert_eq!(args.capabilities(env("")).colors, ColorSupport::Ansi256);
+    let capabilities = args.capabilities(env("1"));
+    assert_eq!(capabilities.colors, ColorSupport::Monochrome);
+    assert!(!capabilities.unicode);
+    let args = Args::parse_from(["tui-rain-cli", "--compat", "full", "--no-color"]);
+    assert_eq!(args.capabilities(env("")).colors, ColorSupport::Monochrome);
+  }
 }
@@ -0,0 +1,248 @@
+use std::str::FromStr;
+
+use ratatui::{
+  buffer::Buffer,
+  layout::Rect,
+  style::{Color, Modifier},
+};
+
+use crate::ParseOptionError;
+
+/// How bright dimmed colors are when dim is emulated.
+const EMULATED_DIM: f64 = 0.6;
+
+/// The 16 named colors, in the order of their ANSI indices.
+const ANSI_COLORS: [Color; 16] = [
+  Color::Black,
+  Color::Red,
+  Color::Green,
+  Color::Yellow,
+  Color::Blue,
+  Color::Magenta,
+  Color::Cyan,
+  Color::Gray,
+  Color::DarkGray,
+  Color::LightRed,
+  Color::LightGreen,
+  Color::LightYellow,
+  Color::LightBlue,
+  Color::LightMagenta,
+  Color::LightCyan,
+  Color::White,
+];
+
+/// The levels of each channel in the color cube of the 256-color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// How many colors a terminal can show.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub enum ColorSupport {
+  /// No colors at all.
+  Monochrome,
+
+  /// The 16 named colors.
+  Ansi16,
+
+  /// The 256-color palette.
+  Ansi256,
+
+  /// Any RGB color.
+  #[default]
+  TrueColor,
+}
+
+/// What a terminal can show, so effects can degrade gracefully on simpler ones.
+///
+/// Parses from a comma separated list of limitations, applied to full support:
+/// `truecolor`, `256`, `16` or `mono` for the colors, `ascii` for no unicode and
+/// `no-dim` for no dim. `full` is full support.
+///
+/// ```
+/// use tui_rain_cli::{Capabilities, ColorSupport};
+///
+/// let capabilities: Capabilities = "16,ascii".parse().unwrap();
+/// assert_eq!(capabilities.colors, ColorSupport::Ansi16);
+/// assert!(!capabilities.unicode);
+/// assert!(capabilities.dim);
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Capabilities {
+  /// How many colors the terminal can show.
+  pub colors: ColorSupport,
+  /// Whether the terminal can show unicode glyphs, like kana and emoji.
+  pub unicode: bool,
+  /// Whether the terminal can show dim text.
+  pub dim: bool,
+}
+
+impl Default for Capabilities {
+  fn default() -> Capabilities {
+    Capabilities {
+      colors: ColorSupport::TrueColor,
+      unicode: true,
+      dim: true,
+    }
+  }
+}
+
+impl Capabilities {
+  /// Guess what the terminal can show from its environment variables, using
+  /// `COLORTERM` and `TERM` for the colors, the locale and `TERM` for unicode, and
+  /// `TERM` for dim. Colors are off when `NO_COLOR` is set, as
+  /// [no-color.org](https://no-color.org) asks.
+  ///
+  /// ```
+  /// use tui_rain_cli::{Capabilities, ColorSupport};
+  ///
+  /// let env = |name: &str| match name {
+  ///     "TERM" => Some("linux".to_string()),
+  ///     "LANG" => Some("en_US.UTF-8".to_string()),
+  ///     _ => None,
+  /// };
+  /// let capabilities = Capabilities::detect(env);
+  /// assert_eq!(capabilities.colors, ColorSupport::Ansi16);
+  /// assert!(!capabilities.unicode);
+  /// assert!(!capabilities.dim);
+  ///
+  /// let env = |name: &str| (name == "NO_COLOR").then(|| "1".to_string());
+  /// assert_eq!(Capabilities::detect(env).colors, ColorSupport::Monochrome);
+  /// ```
+  pub fn detect(env: impl Fn(&str) -> Option<String>) -> Capabilities {
+    let term = env("TERM").unwrap_or_default();
+    let color_term = env("COLORTERM").unwrap_or_default();
+    let colors = if term == "dumb" || env("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+      ColorSupport::Monochrome
+    } else if color_term == "truecolor" || color_term == "24bit" || term.ends_with("direct") {
+      ColorSupport::TrueColor
+    } else if term.contains("256color") {
+      ColorSupport::Ansi256
+    } else if term.is_empty() {
+      ColorSupport::Monochrome
+    } else {
+      ColorSupport::Ansi16
+    };
+    // The first locale variable that is set decides the encoding. Without any, the
+    // terminal is trusted to handle unicode.
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+      .into_iter()
+      .find_map(|name| env(name).filter(|value| !value.is_empty()));
+    let utf8 = locale.is_none_or(|locale| {
+      let locale = locale.to_lowercase();
+      locale.contains("utf-8") || locale.contains("utf8")
+    });
+    // The Linux console only has a small font, without kana or emoji.
+    let console = term == "linux";
+    Capabilities {
+      colors,
+      unicode: utf8 && !console,
+      dim: !console && term != "dumb" && !term.starts_with("vt"),
+    }
+  }
+
+  /// Degrade the colors and styles of an area of a buffer to what the terminal can
+  /// show. Dim is emulated with darker colors, and colors are downsampled to the
+  /// nearest ones available.
+  ///
+  /// ```
+  /// use ratatui::{buffer::Buffer, layout::Rect, style::{Color, Style}};
+  /// use tui_rain_cli::Capabilities;
+  ///
+  /// let area = Rect::new(0, 0, 1, 1);
+  /// let mut buf = Buffer::empty(area);
+  /// buf.set_style(area, Style::default().fg(Color::Rgb(250, 10, 10)));
+  ///
+  /// "16".parse::<Capabilities>().unwrap().degrade(area, &mut buf);
+  /// assert_eq!(buf[(0, 0)].fg, Color::LightRed);
+  /// ```
+  pub fn degrade(&self, area: Rect, buf: &mut Buffer) {
+    if *self == Capabilities::default() {
+      return;
+    }
+    for position in area.positions() {
+      let cell = &mut buf[position];
+      if !self.dim && cell.modifier.contains(Modifier::DIM) {
+        cell.modifier.remove(Modifier::DIM);
+        if self.colors != ColorSupport::Monochrome {
+          cell.fg = crate::shade(rgb_of(cell.fg).map_or(Color::Gray, |(r, g, b)| Color::Rgb(r, g, b)), EMULATED_DIM);
+        }
+      }
+      cell.fg = self.color(cell.fg);
+      cell.bg = self.color(cell.bg);
+    }
+  }
+
+  /// The nearest color the terminal can show.
+  fn color(&self, color: Color) -> Color {
+    match (self.colors, color) {
+      (_, Color::Reset) | (ColorSupport::TrueColor, _) => color,
+      (ColorSupport::Monochrome, _) => Color::Reset,
+      (ColorSupport::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(nearest_indexed((r, g, b))),
+      (ColorSupport::Ansi256, _) => color,
+      (ColorSupport::Ansi16, Color::Rgb(..) | Color::Indexed(_)) => match rgb_of(color) {
+        Some(rgb) => *ANSI_COLORS.iter().min_by_key(|ansi| distance(rgb, crate::rgb(**ansi).unwrap())).unwrap(),
+        None => color,
+      },
+      (ColorSupport::Ansi16, _) => color,
+    }
+  }
+}
+
+impl FromStr for Capabilities {
+  type Err = ParseOptionError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut capabilities = Capabilities::default();
+    for limitation in s.split(',').map(|limitation| limitation.trim().to_lowercase()) {
+      match limitation.as_str() {
+        "full" => capabilities = Capabilities::default(),
+        "truecolor" => capabilities.colors = ColorSupport::TrueColor,
+        "256" => capabilities.colors = ColorSupport::Ansi256,
+        "16" => capabilities.colors = ColorSupport::Ansi16,
+        "mono" => capabilities.colors = ColorSupport::Monochrome,
+        "ascii" => capabilities.unicode = false,
+        "no-dim" => capabilities.dim = false,
+        _ => return Err(ParseOptionError::new("compatibility", s)),
+      }
+    }
+    Ok(capabilities)
+  }
+}
+
+/// The RGB value of a color, including the colors of the 256-color palette.
+fn rgb_of(color: Color) -> Option<(u8, u8, u8)> {
+  match color {
+    Color::Indexed(index @ 0..16) => crate::rgb(ANSI_COLORS[index as usize]),
+    Color::Indexed(index @ 16..232) => {
+      let index = index - 16;
+      Some((
+        CUBE_LEVELS[index as usize / 36],
+        CUBE_LEVELS[index as usize / 6 % 6],
+        CUBE_LEVELS[index as usize % 6],
+      ))
+    }
+    Color::Indexed(index) => {
+      let level = 8 + 10 * (index - 232);
+      Some((level, level, level))
+    }
+    color => crate::rgb(color),
+  }
+}
+
+/// The color of the 256-color palette nearest to an RGB value, from its color cube
+/// or its gray ramp.
+fn nearest_indexed(rgb: (u8, u8, u8)) -> u8 {
+  let level = |channel: u8| (0..6).min_by_key(|&i| CUBE_LEVELS[i].abs_diff(channel)).unwrap();
+  let cube = 16 + 36 * level(rgb.0) + 6 * level(rgb.1) + level(rgb.2);
+  let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+  let gray = 232 + ((average.saturating_sub(3) / 10).min(23)) as u8;
+  [cube as u8, gray]
+    .into_iter()
+    .min_by_key(|&index| distance(rgb, rgb_of(Color::Indexed(index)).unwrap()))
+    .unwrap()
+}
+
+/// The squared distance between two RGB values.
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+  let channel = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+  channel(a.0, b.0) + channel(a.1, b.1) + channel(a.2, b.2)
+}
@@ -0,0 +1,367 @@
+use std::{fmt, time::Duration};
+
+use ratatui::{buffer::Buffer, layout::Rect, style::Color, widgets::Widget};
+
+use crate::{Canvas, CharacterSet, ColumnLock, FallDirection, Fire, Fireworks, Rain, RainDensity, RainSpeed, Resolution, Starfield, Tumble};
+
+/// A precipitation effect that can be rendered at any point in time.
+///
+/// Effects are stateless: everything they draw is derived from the elapsed time, so the
+/// same effect renders the same frame for the same `elapsed`. [`Rain`] is the built-in
+/// effect, and new kinds can be added by implementing this trait and [registering
+/// them](EffectRegistry::with_effect).
+///
+/// ```
+/// use std::time::Duration;
+/// use ratatui::{buffer::Buffer, layout::Rect};
+/// use tui_rain_cli::Effect;
+///
+/// /// Lightning that flashes the whole screen every few seconds.
+/// struct Lightning;
+///
+/// impl Effect for Lightning {
+///     fn render_at(&self, elapsed: Duration, area: Rect, buf: &mut Buffer) {
+///         if elapsed.as_millis() % 5000 < 100 {
+///             buf.set_style(area, ratatui::style::Style::default().bg(ratatui::style::Color::White));
+///         }
+///     }
+/// }
+/// ```
+pub trait Effect {
+  /// Render the effect as it looks `elapsed` after it started.
+  fn render_at(&self, elapsed: Duration, area: Rect, buf: &mut Buffer);
+
+  /// Render the effect as it looks `elapsed` after it started, drawing what it can in
+  /// pixels on `canvas` for terminals with a [graphics protocol](crate::GraphicsProtocol).
+  ///
+  /// By default, everything is rendered in cells.
+  fn render_pixels_at(&self, elapsed: Duration, area: Rect, buf: &mut Buffer, _canvas: &mut Canvas) {
+    self.render_at(elapsed, area, buf);
+  }
+}
+
+impl Effect for Rain {
+  fn render_at(&self, elapsed: Duration, area: Rect, buf: &mut Buffer) {
+    let rain = Rain { elapsed, ..self.clone() };
+    rain.render(area, buf);
+  }
+
+  fn render_pixels_at(&self, elapsed: Duration, area: Rect, buf: &mut Buffer, canvas: &mut Canvas) {
+    let rain = Rain { elapsed, ..self.clone() };
+    rain.draw(area, buf, Some(canvas));
+  }
+}
+
+/// Settings shared by all effects, so they can be configured the same way.
+///
+/// Each effect interprets them as it sees fit, e.g. the density of rain is the number
+/// of drops while the density of fireworks is the number of rockets.
+#[derive(Clone, PartialEq, Debug)]
+pub struct EffectConfig {
+  /// Seed for the effect's randomness.
+  pub seed: u64,
+  /// How much is going on at once. The CLI expects a relative density.
+  pub density: RainDensity,
+  /// How fast things move.
+  pub speed: RainSpeed,
+  /// How much the speed varies, as a fraction of it.
+  pub speed_variance: f64,
+  /// How long trails last.
+  pub tail_lifespan: Duration,
+  /// The main color.
+  pub color: Color,
+  /// The color of whatever leads, like drop heads.
+  pub head_color: Color,
+  /// Whether to fade with bold and dim.
+  pub bold_dim_effect: bool,
+  /// The color of messages shown over the effect.
+  pub message_color: Color,
+  /// How finely things are placed within cells.
+  pub resolution: Resolution,
+}
+
+impl Default for EffectConfig {
+  fn default() -> EffectConfig {
+    EffectConfig {
+      seed: 1234,
+      density: RainDensity::Relative { sparseness: 30 },
+      speed: RainSpeed::Absolute { speed: 2.0 },
+      speed_variance: 0.3,
+      tail_lifespan: Duration::from_millis(500),
+      color: Color::White,
+      head_color: Color::White,
+      bold_dim_effect: true,
+      message_color: Color::Gray,
+      resolution: Resolution::Cell,
+    }
+  }
+}
+
+/// Builds a rain preset at a point in time from the settings in use.
+type RainBuilder = Box<dyn Fn(Duration, &EffectConfig) -> Rain>;
+
+/// Builds an effect from the settings in use.
+type EffectBuilder = Box<dyn Fn(&EffectConfig) -> Box<dyn Effect>>;
+
+/// How a registered effect is built.
+enum Builder {
+  Rain(RainBuilder),
+  Other(EffectBuilder),
+}
+
+/// An effect in a [registry](EffectRegistry).
+struct RegisteredEffect {
+  name: String,
+  about: String,
+  defaults: EffectConfig,
+  builder: Builder,
+}
+
+/// Effects that can be looked up by name, like the CLI's `--rain-type`.
+///
+/// ```
+/// use std::time::Duration;
+/// use tui_rain_cli::{CharacterSet, EffectConfig, EffectRegistry, Rain};
+///
+/// let effects = EffectRegistry::builtin().with_rain_effect(
+///     "hail",
+///     "Hail effect",
+///     EffectConfig::default(),
+///     |elapsed, config| {
+///         Rain::new_rain(elapsed)
+///             .with_config(config)
+///             .with_character_set(CharacterSet::Explicit { options: vec!['o'] })
+///     },
+/// );
+///
+/// let config = effects.defaults("hail").unwrap();
+/// let hail = effects.build("hail", config);
+/// ```
+#[derive(Default)]
+pub struct EffectRegistry {
+  effects: Vec<RegisteredEffect>,
+}
+
+impl EffectRegistry {
+  /// Construct an empty registry.
+  pub fn new() -> EffectRegistry {
+    EffectRegistry::default()
+  }
+
+  /// Construct a registry with the built-in presets: `rain`, `matrix`, `classic`,
+  /// `snow`, `flurry`, `data`, `emoji`, `leaves`, `petals`, `confetti`, `bubbles`,
+  /// `fireworks`, `starfield`, `warp` and `fire`.
+  pub fn builtin() -> EffectRegistry {
+    let preset = |density, speed, speed_variance, tail_millis, color, message_color| EffectConfig {
+      density: RainDensity::Relative { sparseness: density },
+      speed: RainSpeed::Absolute { speed },
+      speed_variance,
+      tail_lifespan: Duration::from_millis(tail_millis),
+      color,
+      message_color,
+      ..EffectConfig::default()
+    };
+    EffectRegistry::new()
+      .with_rain_effect(
+        "rain",
+        "Rain effect",
+        preset(30, 2.0, 10.0, 500, Color::LightBlue, Color::Blue),
+        |elapsed, config| Rain::new_rain(elapsed).with_config(config),
+      )
+      .with_rain_effect(
+        "matrix",
+        "Matrix effect",
+        preset(50, 5.0, 0.5, 3000, Color::LightGreen, Color::Green),
+        |elapsed, config| Rain::new_matrix(elapsed).with_config(config),
+      )
+      .with_rain_effect(
+        "classic",
+        "Classic matrix effect, with one stream per column",
+        preset(100, 8.0, 0.5, 2000, Color::LightGreen, Color::Green),
+        |elapsed, config| Rain::new_matrix(elapsed).with_config(config).with_column_lock(ColumnLock::default()),
+      )
+      .with_rain_effect(
+        "snow",
+        "Snow effect",
+        preset(30, 2.0, 0.3, 500, Color::White, Color::Gray),
+        |elapsed, config| Rain::new_snow(elapsed).with_config(config),
+      )
+      .with_rain_effect(
+        "flurry",
+        "Fine snow drawn with braille dots",
+        EffectConfig {
+          resolution: Resolution::Braille,
+          ..preset(10, 1.5, 0.3, 1000, Color::White, Color::Gray)
+        },
+        |elapsed, config| Rain::new_snow(elapsed).with_config(config),
+      )
+      .with_rain_effect(
+        "data",
+        "Data effect",
+        preset(70, 2.0, 3.0, 1000, Color::LightBlue, Color::Blue),
+        |elapsed, config| {
+          Rain::new_matrix(elapsed)
+            .with_config(config)
+            .with_character_set(CharacterSet::Explicit { options: vec!['0', '1'] })
+        },
+      )
+      .with_rain_effect(
+        "emoji",
+        "Emoji effect",
+        preset(20, 10.0, 0.1, 500, Color::White, Color::Yellow),
+        |elapsed, config| Rain::new_emoji_soup(elapsed).with_config(config),
+      )
+      .with_rain_effect(
+        "leaves",
+        "Falling leaves effect",
+        preset(60, 3.0, 0.3, 400, Color::Rgb(205, 92, 30), Color::Yellow),
+        |elapsed, config| {
+          tumbling(elapsed, config, ['/', '-', '\\', '|'], 2.0, 3000).with_color_palette(vec![
+            Color::Rgb(205, 92, 30),
+            Color::Rgb(178, 34, 34),
+            Color::Rgb(218, 165, 32),
+            Color::Rgb(139, 69, 19),
+          ])
+        },
+      )
+      .with_rain_effect(
+        "petals",
+        "Sakura petals effect",
+        preset(50, 2.0, 0.3, 500, Color::Rgb(255, 183, 197), Color::LightMagenta),
+        |elapsed, config| {
+          tumbling(elapsed, config, ['❀', '✿', '❁', '✾'], 3.0, 4000).with_color_palette(vec![
+            Color::Rgb(255, 183, 197),
+            Color::Rgb(255, 209, 220),
+            Color::Rgb(250, 218, 221),
+            Color::White,
+          ])
+        },
+      )
+      .with_rain_effect(
+        "confetti",
+        "Confetti effect",
+        preset(25, 4.0, 0.3, 300, Color::White, Color::White),
+        |elapsed, config| {
+          tumbling(elapsed, config, ['▘', '▝', '▗', '▖'], 1.0, 1500).with_color_palette(vec![
+            Color::LightRed,
+            Color::LightYellow,
+            Color::LightGreen,
+            Color::LightBlue,
+            Color::LightMagenta,
+            Color::LightCyan,
+          ])
+        },
+      )
+      .with_rain_effect(
+        "bubbles",
+        "Bubbles effect",
+        preset(40, 3.0, 0.4, 400, Color::LightCyan, Color::LightBlue),
+        |elapsed, config| {
+          tumbling(elapsed, config, ['o', 'O', '°', 'O'], 0.6, 2000)
+            .with_direction(FallDirection::Up)
+            .with_head_color(config.color)
+        },
+      )
+      .with_effect(
+        "fireworks",
+        "Fireworks effect",
+        preset(400, 12.0, 0.2, 250, Color::Yellow, Color::LightYellow),
+        |config| Box::new(Fireworks::new(Duration::ZERO).with_config(config)),
+      )
+      .with_effect(
+        "starfield",
+        "Starfield effect",
+        preset(20, 20.0, 0.3, 300, Color::White, Color::LightYellow),
+        |config| Box::new(Starfield::new(Duration::ZERO).with_config(config)),
+      )
+      .with_effect(
+        "warp",
+        "Warp speed effect",
+        preset(30, 60.0, 0.3, 150, Color::LightCyan, Color::White),
+        |config| Box::new(Starfield::new(Duration::ZERO).with_config(config).with_warp(true)),
+      )
+      .with_effect("fire", "Fire effect", preset(30, 15.0, 0.0, 500, Color::Red, Color::LightYellow), |config| {
+        Box::new(Fire::new(Duration::ZERO).with_config(config))
+      })
+  }
+
+  /// Register an effect under `name`, replacing any effect with the same name.
+  ///
+  /// `defaults` are the settings the effect looks best with, and `build` constructs
+  /// the effect from the settings in use.
+  pub fn with_effect(self, name: &str, about: &str, defaults: EffectConfig, build: impl Fn(&EffectConfig) -> Box<dyn Effect> + 'static) -> EffectRegistry {
+    self.with_builder(name, about, defaults, Builder::Other(Box::new(build)))
+  }
+
+  /// Register a [`Rain`] preset under `name`, replacing any effect with the same name.
+  ///
+  /// Unlike other effects, rain presets can be [built as rain](EffectRegistry::build_rain)
+  /// to configure rain specific settings, like messages.
+  pub fn with_rain_effect(self, name: &str, about: &str, defaults: EffectConfig, build: impl Fn(Duration, &EffectConfig) -> Rain + 'static) -> EffectRegistry {
+    self.with_builder(name, about, defaults, Builder::Rain(Box::new(build)))
+  }
+
+  fn with_builder(mut self, name: &str, about: &str, defaults: EffectConfig, builder: Builder) -> EffectRegistry {
+    self.effects.retain(|effect| effect.name != name);
+    self.effects.push(RegisteredEffect {
+      name: name.to_string(),
+      about: about.to_string(),
+      defaults,
+      builder,
+    });
+    self
+  }
+
+  /// Get the names of the registered effects, in the order they were registered.
+  pub fn names(&self) -> impl Iterator<Item = &str> {
+    self.effects.iter().map(|effect| effect.name.as_str())
+  }
+
+  /// Get the description of an effect.
+  pub fn about(&self, name: &str) -> Option<&str> {
+    self.get(name).map(|effect| effect.about.as_str())
+  }
+
+  /// Get the default settings of an effect.
+  pub fn defaults(&self, name: &str) -> Option<&EffectConfig> {
+    self.get(name).map(|effect| &effect.defaults)
+  }
+
+  /// Build an effect with the given settings.
+  pub fn build(&self, name: &str, config: &EffectConfig) -> Option<Box<dyn Effect>> {
+    self.get(name).map(|effect| match &effect.builder {
+      Builder::Rain(build) => Box::new(build(Duration::ZERO, config)) as Box<dyn Effect>,
+      Builder::Other(build) => build(config),
+    })
+  }
+
+  /// Build an effect as [`Rain`] at `elapsed`, or `None` if it isn't a rain preset.
+  pub fn build_rain(&self, name: &str, elapsed: Duration, config: &EffectConfig) -> Option<Rain> {
+    self.get(name).and_then(|effect| match &effect.builder {
+      Builder::Rain(build) => Some(build(elapsed, config)),
+      Builder::Other(_) => None,
+    })
+  }
+
+  fn get(&self, name: &str) -> Option<&RegisteredEffect> {
+    self.effects.iter().find(|effect| effect.name == name)
+  }
+}
+
+/// Build a preset of particles that tumble through `glyphs` as they fall.
+fn tumbling(elapsed: Duration, config: &EffectConfig, glyphs: [char; 4], sway: f64, sway_period_millis: u64) -> Rain {
+  Rain::new_snow(elapsed)
+    .with_config(config)
+    .with_character_set(CharacterSet::Explicit { options: glyphs.to_vec() })
+    .with_tumble(Tumble {
+      sway,
+      sway_period: Duration::from_millis(sway_period_millis),
+      spin_interval: Duration::from_millis(400),
+    })
+}
+
+impl fmt::Debug for EffectRegistry {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_list().entries(self.names()).finish()
+  }
+}
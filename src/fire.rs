@@ -0,0 +1,308 @@
+use std::time::Duration;
+
+use ratatui::{
+  buffer::Buffer,
+  layout::Rect,
+  style::{Color, Style},
+  widgets::Widget,
+};
+
+use crate::{Effect, EffectConfig, RainSpeed};
+
+/// The heat of the fire source at full strength.
+const MAX_HEAT: u8 = 36;
+
+/// Glyphs from the coolest to the hottest part of the fire.
+const FIRE_GLYPHS: [char; 6] = [' ', '.', ':', '*', '#', '@'];
+
+/// Colors the heat fades through, from the hottest to the coolest.
+const FIRE_COLORS: [(u8, u8, u8); 6] = [(255, 255, 200), (255, 220, 60), (255, 150, 20), (230, 80, 10), (170, 30, 5), (80, 10, 5)];
+
+/// A doom fire widget.
+///
+/// Heat rises from the bottom row, spreading randomly and cooling as it goes, and is
+/// drawn with glyphs and colors from white hot yellow to dark red. Like
+/// [`Rain`](crate::Rain), the fire is computed from the elapsed time, so no state is
+/// kept between frames.
+///
+/// ```
+/// use std::time::Duration;
+/// use tui_rain_cli::Fire;
+///
+/// let elapsed = Duration::from_secs(5);
+///
+/// Fire::new(elapsed);
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct Fire {
+  elapsed: Duration,
+  seed: u64,
+  speed: RainSpeed,
+  flame_height: f64,
+  wind: f64,
+  transition: Duration,
+  extinguish_at: Option<Duration>,
+}
+
+impl Fire {
+  /// Construct a new fire widget with defaults.
+  pub fn new(elapsed: Duration) -> Fire {
+    Fire {
+      elapsed,
+      seed: 1234,
+      speed: RainSpeed::Absolute { speed: 15.0 },
+      flame_height: 0.6,
+      wind: 0.0,
+      transition: Duration::from_secs(2),
+      extinguish_at: None,
+    }
+  }
+
+  /// Set the random seed for the fire.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::Fire;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Fire::new(elapsed)
+  ///     .with_seed(2027);
+  /// ```
+  pub fn with_seed(mut self, seed: u64) -> Fire {
+    self.seed = seed;
+    self
+  }
+
+  /// Set how fast heat rises, in cells / second.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{Fire, RainSpeed};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Fire::new(elapsed)
+  ///     .with_speed(RainSpeed::Absolute { speed: 30.0 });
+  /// ```
+  pub fn with_speed(mut self, speed: RainSpeed) -> Fire {
+    self.speed = speed;
+    self
+  }
+
+  /// Set how high the flames reach on average, as a fraction of the height.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::Fire;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Fire::new(elapsed)
+  ///     .with_flame_height(0.3);
+  /// ```
+  pub fn with_flame_height(mut self, flame_height: f64) -> Fire {
+    self.flame_height = flame_height;
+    self
+  }
+
+  /// Set how far the flames drift sideways, in cells per row. Positive values blow to
+  /// the right and negative values to the left.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::Fire;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Fire::new(elapsed)
+  ///     .with_wind(-0.5);
+  /// ```
+  pub fn with_wind(mut self, wind: f64) -> Fire {
+    self.wind = wind;
+    self
+  }
+
+  /// Set how long the fire takes to ignite at the start, and to die down when it is
+  /// [extinguished](Fire::with_extinguish_at).
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::Fire;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Fire::new(elapsed)
+  ///     .with_transition(Duration::from_secs(5));
+  /// ```
+  pub fn with_transition(mut self, transition: Duration) -> Fire {
+    self.transition = transition;
+    self
+  }
+
+  /// Set when the fire starts to die down. By default, it burns forever.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::Fire;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Fire::new(elapsed)
+  ///     .with_extinguish_at(Duration::from_secs(60));
+  /// ```
+  pub fn with_extinguish_at(mut self, extinguish_at: Duration) -> Fire {
+    self.extinguish_at = Some(extinguish_at);
+    self
+  }
+
+  /// Apply the settings shared by all [effects](Effect).
+  ///
+  /// Only the seed and the speed apply, as the fire has its own palette.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{EffectConfig, Fire};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Fire::new(elapsed)
+  ///     .with_config(&EffectConfig::default());
+  /// ```
+  pub fn with_config(self, config: &EffectConfig) -> Fire {
+    self.with_seed(config.seed).with_speed(config.speed)
+  }
+
+  /// How strong the fire source is at `elapsed` seconds, from 0 to 1.
+  fn source_strength(&self, elapsed: f64) -> f64 {
+    let transition = self.transition.as_secs_f64();
+    let ramp = |t: f64| {
+      if transition > 0.0 {
+        (t / transition).clamp(0.0, 1.0)
+      } else {
+        (t >= 0.0) as u8 as f64
+      }
+    };
+    let extinguished = self.extinguish_at.map_or(0.0, |at| ramp(elapsed - at.as_secs_f64()));
+    ramp(elapsed) * (1.0 - extinguished)
+  }
+
+  /// Compute the heat of every cell at `step`.
+  ///
+  /// Each step, every cell takes the heat of a random neighbour of the cell below it,
+  /// cooled a bit. The heat of a cell therefore left the bottom row as many steps ago
+  /// as it is rows above it, and has cooled along a path picked by the randomness of
+  /// each step since. Tracing that path back stops as soon as the heat is used up, so
+  /// only the rows a flame reaches cost anything, and it gives the same result as
+  /// running the fire from the start.
+  fn heat(&self, step: u64, width: usize, height: usize) -> Vec<u8> {
+    let speed = self.speed.speed().max(1e-3);
+    let cooling = MAX_HEAT as f64 / (height as f64 * self.flame_height.max(1e-3));
+    let mut heat = vec![0u8; width * height];
+    for row in 0..height {
+      // Cells whose heat would have left the bottom row before the start are cold.
+      let Some(start) = step.checked_sub((height - 1 - row) as u64) else {
+        continue;
+      };
+      let source = (MAX_HEAT as f64 * self.source_strength(start as f64 / speed)).round() as u8;
+      for column in 0..width {
+        let (mut x, mut y, mut step, mut remaining) = (column, row, step, source);
+        while remaining > 0 && y < height - 1 {
+          let random = hash(self.seed, step, (y * width + x) as u64);
+          let shift = (crate::uniform(random, -1.5, 1.5) - self.wind).round() as isize;
+          // Cool by a random amount around the average, so flames break up into tongues.
+          let cooled = (cooling * crate::uniform(random.rotate_left(32), 0.0, 2.0)).round() as u8;
+          remaining = remaining.saturating_sub(cooled);
+          (x, y, step) = ((x as isize + shift).clamp(0, width as isize - 1) as usize, y + 1, step - 1);
+        }
+        heat[row * width + column] = remaining;
+      }
+    }
+    heat
+  }
+}
+
+impl Widget for Fire {
+  fn render(self, area: Rect, buf: &mut Buffer) {
+    if area.is_empty() {
+      return;
+    }
+    let step = (self.elapsed.as_secs_f64() * self.speed.speed()) as u64;
+    let (width, height) = (area.width as usize, area.height as usize);
+    for (i, heat) in self.heat(step, width, height).into_iter().enumerate() {
+      if heat == 0 {
+        continue;
+      }
+      let level = heat as f64 / MAX_HEAT as f64;
+      let glyph = FIRE_GLYPHS[((level * FIRE_GLYPHS.len() as f64).ceil() as usize).clamp(1, FIRE_GLYPHS.len() - 1)];
+      let (r, g, b) = FIRE_COLORS[(((1.0 - level) * FIRE_COLORS.len() as f64) as usize).min(FIRE_COLORS.len() - 1)];
+      let (x, y) = (area.x + (i % width) as u16, area.y + (i / width) as u16);
+      buf[(x, y)].set_char(glyph).set_style(Style::default().fg(Color::Rgb(r, g, b)));
+    }
+  }
+}
+
+impl Effect for Fire {
+  fn render_at(&self, elapsed: Duration, area: Rect, buf: &mut Buffer) {
+    Fire { elapsed, ..self.clone() }.render(area, buf);
+  }
+}
+
+/// Hash a cell of a step of the fire into uniform random bits.
+fn hash(seed: u64, step: u64, cell: u64) -> u64 {
+  // SplitMix64, which is cheap enough to run for every cell of every step.
+  let mut z = seed ^ step.wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ cell.wrapping_mul(0xc2b2_ae3d_27d4_eb4f);
+  z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+  z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+  z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Run the fire step by step from the start, the slow way.
+  fn simulate(fire: &Fire, steps: u64, width: usize, height: usize) -> Vec<u8> {
+    let speed = fire.speed.speed().max(1e-3);
+    let cooling = MAX_HEAT as f64 / (height as f64 * fire.flame_height.max(1e-3));
+    let mut heat = vec![0u8; width * height];
+    for step in 0..=steps {
+      let mut next = heat.clone();
+      next[(height - 1) * width..].fill((MAX_HEAT as f64 * fire.source_strength(step as f64 / speed)).round() as u8);
+      for y in 0..height - 1 {
+        for x in 0..width {
+          let random = hash(fire.seed, step, (y * width + x) as u64);
+          let shift = (crate::uniform(random, -1.5, 1.5) - fire.wind).round() as isize;
+          let from = (x as isize + shift).clamp(0, width as isize - 1) as usize;
+          let cooled = (cooling * crate::uniform(random.rotate_left(32), 0.0, 2.0)).round() as u8;
+          next[y * width + x] = heat[(y + 1) * width + from].saturating_sub(cooled);
+        }
+      }
+      heat = next;
+    }
+    heat
+  }
+
+  #[test]
+  fn heat_matches_running_the_fire_from_the_start() {
+    let fires = [
+      Fire::new(Duration::ZERO),
+      Fire::new(Duration::ZERO).with_flame_height(0.1).with_wind(0.7),
+      Fire::new(Duration::ZERO).with_flame_height(3.0).with_wind(-1.2).with_seed(7),
+      Fire::new(Duration::ZERO)
+        .with_transition(Duration::ZERO)
+        .with_extinguish_at(Duration::from_secs(4)),
+    ];
+    for fire in &fires {
+      for (width, height) in [(1, 1), (7, 3), (24, 16)] {
+        for step in [0, 2, 15, 40, 90] {
+          assert_eq!(
+            fire.heat(step, width, height),
+            simulate(fire, step, width, height),
+            "{fire:?} at step {step} in {width}x{height}"
+          );
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,516 @@
+use std::{f64::consts::TAU, time::Duration};
+
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use ratatui::{
+  buffer::Buffer,
+  layout::Rect,
+  style::{Color, Style, Stylize},
+  widgets::Widget,
+};
+
+use crate::{CharacterSet, Effect, EffectConfig, Interpolate, RainDensity, RainSpeed};
+
+/// How quickly burst particles slow down, per second.
+const PARTICLE_DRAG: f64 = 2.0;
+
+/// Time between the samples of a sparkle trail.
+const TRAIL_STEP_SECS: f64 = 0.06;
+
+/// How many times per second sparkles twinkle.
+const SPARKLE_RATE: f64 = 12.0;
+
+/// A fireworks widget.
+///
+/// Rockets launch from the bottom of the screen and burst into shells of particles that
+/// spread out, fall under gravity and fade. Like [`Rain`](crate::Rain), everything is
+/// computed from the elapsed time, so no state is kept between frames.
+///
+/// ```
+/// use std::time::Duration;
+/// use tui_rain_cli::Fireworks;
+///
+/// let elapsed = Duration::from_secs(5);
+///
+/// Fireworks::new(elapsed);
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct Fireworks {
+  elapsed: Duration,
+  seed: u64,
+  launch_density: RainDensity,
+  launch_speed: RainSpeed,
+  launch_speed_variance: f64,
+  rocket_color: Color,
+  colors: Vec<Color>,
+  character_set: CharacterSet,
+  gravity: f64,
+  burst_lifespan: Duration,
+  trail_lifespan: Duration,
+  sparkle_trails: bool,
+  bold_dim_effect: bool,
+}
+
+impl Fireworks {
+  /// Construct a new fireworks widget with defaults.
+  pub fn new(elapsed: Duration) -> Fireworks {
+    Fireworks {
+      elapsed,
+      seed: 1234,
+      launch_density: RainDensity::Relative { sparseness: 400 },
+      launch_speed: RainSpeed::Absolute { speed: 12.0 },
+      launch_speed_variance: 0.2,
+      rocket_color: Color::Yellow,
+      colors: vec![
+        Color::LightRed,
+        Color::LightYellow,
+        Color::LightGreen,
+        Color::LightBlue,
+        Color::LightMagenta,
+        Color::LightCyan,
+        Color::White,
+      ],
+      character_set: CharacterSet::Explicit { options: vec!['*', '+'] },
+      gravity: 4.0,
+      burst_lifespan: Duration::from_millis(2000),
+      trail_lifespan: Duration::from_millis(250),
+      sparkle_trails: true,
+      bold_dim_effect: true,
+    }
+  }
+
+  /// Set the random seed for the fireworks.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::Fireworks;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Fireworks::new(elapsed)
+  ///     .with_seed(2027);
+  /// ```
+  pub fn with_seed(mut self, seed: u64) -> Fireworks {
+    self.seed = seed;
+    self
+  }
+
+  /// Set how many fireworks are shown at once, computed like the number of drops of
+  /// [rain](crate::Rain::with_rain_density).
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{Fireworks, RainDensity};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Fireworks::new(elapsed)
+  ///     .with_launch_density(RainDensity::Absolute { num_drops: 3 });
+  /// ```
+  pub fn with_launch_density(mut self, launch_density: RainDensity) -> Fireworks {
+    self.launch_density = launch_density;
+    self
+  }
+
+  /// Set how fast rockets rise.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{Fireworks, RainSpeed};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Fireworks::new(elapsed)
+  ///     .with_launch_speed(RainSpeed::Absolute { speed: 20.0 });
+  /// ```
+  pub fn with_launch_speed(mut self, launch_speed: RainSpeed) -> Fireworks {
+    self.launch_speed = launch_speed;
+    self
+  }
+
+  /// Set how much the speed of each rocket varies, as a fraction of the launch speed.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::Fireworks;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Fireworks::new(elapsed)
+  ///     .with_launch_speed_variance(0.5);
+  /// ```
+  pub fn with_launch_speed_variance(mut self, launch_speed_variance: f64) -> Fireworks {
+    self.launch_speed_variance = launch_speed_variance;
+    self
+  }
+
+  /// Set the color of rising rockets.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use ratatui::style::Color;
+  /// use tui_rain_cli::Fireworks;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Fireworks::new(elapsed)
+  ///     .with_rocket_color(Color::White);
+  /// ```
+  pub fn with_rocket_color(mut self, rocket_color: Color) -> Fireworks {
+    self.rocket_color = rocket_color;
+    self
+  }
+
+  /// Set the colors shells are drawn from. Each shell picks one at random.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use ratatui::style::Color;
+  /// use tui_rain_cli::Fireworks;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Fireworks::new(elapsed)
+  ///     .with_colors(vec![Color::Red, Color::White, Color::Blue]);
+  /// ```
+  pub fn with_colors(mut self, colors: Vec<Color>) -> Fireworks {
+    self.colors = colors;
+    self
+  }
+
+  /// Set the characters shell particles are drawn with. Each particle picks one at
+  /// random, and fades to `.` at the end of its life.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{CharacterSet, Fireworks};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Fireworks::new(elapsed)
+  ///     .with_character_set(CharacterSet::Explicit { options: vec!['*', 'o', '+'] });
+  /// ```
+  pub fn with_character_set(mut self, character_set: CharacterSet) -> Fireworks {
+    self.character_set = character_set;
+    self
+  }
+
+  /// Set how fast burst particles accelerate downwards, in cells / second².
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::Fireworks;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Fireworks::new(elapsed)
+  ///     .with_gravity(8.0);
+  /// ```
+  pub fn with_gravity(mut self, gravity: f64) -> Fireworks {
+    self.gravity = gravity;
+    self
+  }
+
+  /// Set how long burst particles live at most.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::Fireworks;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Fireworks::new(elapsed)
+  ///     .with_burst_lifespan(Duration::from_secs(3));
+  /// ```
+  pub fn with_burst_lifespan(mut self, burst_lifespan: Duration) -> Fireworks {
+    self.burst_lifespan = burst_lifespan;
+    self
+  }
+
+  /// Set how long the trails behind rockets and particles last.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::Fireworks;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Fireworks::new(elapsed)
+  ///     .with_trail_lifespan(Duration::from_millis(100));
+  /// ```
+  pub fn with_trail_lifespan(mut self, trail_lifespan: Duration) -> Fireworks {
+    self.trail_lifespan = trail_lifespan;
+    self
+  }
+
+  /// Set whether burst particles leave twinkling trails behind them.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::Fireworks;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Fireworks::new(elapsed)
+  ///     .with_sparkle_trails(false);
+  /// ```
+  pub fn with_sparkle_trails(mut self, sparkle_trails: bool) -> Fireworks {
+    self.sparkle_trails = sparkle_trails;
+    self
+  }
+
+  /// Set whether particles fade with bold and dim as they age.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::Fireworks;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Fireworks::new(elapsed)
+  ///     .with_bold_dim_effect(false);
+  /// ```
+  pub fn with_bold_dim_effect(mut self, bold_dim_effect: bool) -> Fireworks {
+    self.bold_dim_effect = bold_dim_effect;
+    self
+  }
+
+  /// Apply the settings shared by all [effects](Effect).
+  ///
+  /// The density sets how many fireworks are shown at once, the speed how fast rockets
+  /// rise, and the color is the color of the rockets.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{EffectConfig, Fireworks};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Fireworks::new(elapsed)
+  ///     .with_config(&EffectConfig::default());
+  /// ```
+  pub fn with_config(self, config: &EffectConfig) -> Fireworks {
+    self
+      .with_seed(config.seed)
+      .with_launch_density(config.density)
+      .with_launch_speed(config.speed)
+      .with_launch_speed_variance(config.speed_variance)
+      .with_trail_lifespan(config.tail_lifespan)
+      .with_rocket_color(config.color)
+      .with_bold_dim_effect(config.bold_dim_effect)
+  }
+
+  /// Work out the firework a lane launches during one of its cycles.
+  fn build_shell(&self, lane: u64, cycle: u64, cycle_time: f64, width: u16, height: u16) -> Shell {
+    let mut rng = Pcg64Mcg::seed_from_u64(self.seed ^ lane.wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ cycle.wrapping_mul(0xc2b2_ae3d_27d4_eb4f));
+    let (width, height) = (width as f64, height as f64);
+    // A negative or zero speed would never reach the burst height.
+    let speed = self.launch_speed.speed().max(1e-3);
+    let variance = self.launch_speed_variance.clamp(0.0, 0.9);
+
+    let burst_y = rng.gen_range(height * 0.15..=height * 0.5);
+    let rise_speed = rng.gen_range(speed * (1.0 - variance)..=speed * (1.0 + variance));
+    let rise_time = (height - 1.0 - burst_y) / rise_speed;
+    let lifespan = self.burst_lifespan.as_secs_f64();
+    // Launch at a random point of the cycle, leaving enough time for the burst to end.
+    let latest_launch = (cycle_time - rise_time - lifespan).max(0.0);
+    // Burst particles spread about a quarter of the screen height before slowing down.
+    let burst_speed = height * 0.5 * rng.gen_range(0.7..=1.0);
+
+    Shell {
+      launch: cycle as f64 * cycle_time + rng.gen_range(0.0..=latest_launch),
+      x: rng.gen_range(width * 0.1..=width * 0.9),
+      rise_speed,
+      rise_time,
+      burst_y,
+      burst_speed,
+      color: self
+        .colors
+        .get(rng.gen_range(0..self.colors.len().max(1)))
+        .copied()
+        .unwrap_or(self.rocket_color),
+      particles: (0..rng.gen_range(16..=32u32)).map(|_| rng.gen()).collect(),
+    }
+  }
+
+  /// Build the glyphs of a shell at `elapsed` seconds.
+  fn build_glyphs(&self, shell: &Shell, elapsed: f64, width: u16, height: u16) -> Vec<Glyph> {
+    let mut glyphs = vec![];
+    let since_launch = elapsed - shell.launch;
+    if since_launch < 0.0 {
+      return glyphs;
+    }
+    let trail_len = (self.trail_lifespan.as_secs_f64() / TRAIL_STEP_SECS) as usize;
+    let bottom = height as f64 - 1.0;
+
+    // The rocket rises with a trail of sparks behind it.
+    if since_launch < shell.rise_time {
+      let y = bottom - shell.rise_speed * since_launch;
+      let style = Style::default().fg(self.rocket_color);
+      glyphs.extend(Glyph::new(shell.x, y, '|', style.bold(), width, height));
+      for step in 1..=(self.trail_lifespan.as_secs_f64() * shell.rise_speed) as usize {
+        let spark = if self.twinkles(shell.launch.to_bits(), step as u64, elapsed) {
+          '\''
+        } else {
+          '.'
+        };
+        glyphs.extend(Glyph::new(shell.x, y + step as f64, spark, style.dim(), width, height));
+      }
+      return glyphs;
+    }
+
+    // Then it bursts into particles flying out in all directions.
+    let since_burst = since_launch - shell.rise_time;
+    let lifespan = self.burst_lifespan.as_secs_f64();
+    for (i, seed) in shell.particles.iter().enumerate() {
+      let angle = TAU * (i as f64 + crate::uniform(*seed, 0.0, 0.5)) / shell.particles.len() as f64;
+      let speed = shell.burst_speed * crate::uniform(seed.rotate_left(16), 0.8, 1.0);
+      let life = lifespan * crate::uniform(seed.rotate_left(32), 0.7, 1.0);
+      if since_burst >= life {
+        continue;
+      }
+      let position = |t: f64| {
+        // Drag slows particles exponentially, which integrates to this closed form.
+        let spread = speed * (1.0 - (-PARTICLE_DRAG * t).exp()) / PARTICLE_DRAG;
+        // Cells are about twice as tall as they are wide, so spread twice as far sideways.
+        let x = shell.x + 2.0 * spread * angle.cos();
+        let y = shell.burst_y + spread * angle.sin() + 0.5 * self.gravity * t * t;
+        (x, y)
+      };
+
+      let age = since_burst / life;
+      let color = match age {
+        age if age < 0.5 => shell.color,
+        age => shell.color.interpolate(&Color::Black, (age - 0.5) * 1.5),
+      };
+      let mut style = Style::default().fg(color);
+      if self.bold_dim_effect {
+        style = match age {
+          age if age < 0.3 => style.bold(),
+          age if age > 0.7 => style.dim(),
+          _ => style,
+        };
+      }
+
+      if self.sparkle_trails {
+        for step in 1..=trail_len {
+          let t = since_burst - step as f64 * TRAIL_STEP_SECS;
+          if t > 0.0 && self.twinkles(*seed, step as u64, elapsed) {
+            let (x, y) = position(t);
+            glyphs.extend(Glyph::new(x, y, '.', style.dim().not_bold(), width, height));
+          }
+        }
+      }
+
+      let content = match age {
+        age if age < 0.7 => self.character_set.get(*seed as u32),
+        _ => '.',
+      };
+      let (x, y) = position(since_burst);
+      glyphs.extend(Glyph::new(x, y, content, style, width, height));
+    }
+    glyphs
+  }
+
+  /// Whether a spark is lit at `elapsed`, so trails twinkle.
+  fn twinkles(&self, seed: u64, step: u64, elapsed: f64) -> bool {
+    let frame = (elapsed * SPARKLE_RATE) as u64;
+    (seed ^ step.wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ frame.wrapping_mul(0xc2b2_ae3d_27d4_eb4f))
+      .count_ones()
+      .is_multiple_of(2)
+  }
+}
+
+impl Widget for Fireworks {
+  fn render(self, area: Rect, buf: &mut Buffer) {
+    if area.is_empty() {
+      return;
+    }
+    let elapsed = self.elapsed.as_secs_f64();
+    let lanes = self.launch_density.num_drops(area).max(1) as u64;
+
+    // Every lane launches one firework per cycle. A cycle is long enough for the
+    // slowest rocket to rise and burst, plus a random pause that differs per lane.
+    let slowest_rise = area.height as f64 / (self.launch_speed.speed() * (1.0 - self.launch_speed_variance.clamp(0.0, 0.9))).max(1e-3);
+    let busy_time = slowest_rise + self.burst_lifespan.as_secs_f64();
+    for lane in 0..lanes {
+      let cycle_time = busy_time * crate::uniform(self.seed.wrapping_add(lane).wrapping_mul(0x2545_f491_4f6c_dd1d), 1.2, 2.0);
+      let cycle = (elapsed / cycle_time) as u64;
+      let shell = self.build_shell(lane, cycle, cycle_time, area.width, area.height);
+      for glyph in self.build_glyphs(&shell, elapsed, area.width, area.height) {
+        buf[(area.x + glyph.x, area.y + glyph.y)].set_char(glyph.content).set_style(glyph.style);
+      }
+    }
+  }
+}
+
+impl Effect for Fireworks {
+  fn render_at(&self, elapsed: Duration, area: Rect, buf: &mut Buffer) {
+    Fireworks { elapsed, ..self.clone() }.render(area, buf);
+  }
+}
+
+/// A single firework: a rocket and the shell it bursts into.
+struct Shell {
+  /// When the rocket launches, in seconds.
+  launch: f64,
+  x: f64,
+  rise_speed: f64,
+  rise_time: f64,
+  burst_y: f64,
+  /// The initial speed of the burst particles.
+  burst_speed: f64,
+  color: Color,
+  /// Stable entropy for each particle.
+  particles: Vec<u64>,
+}
+
+/// A glyph of a firework, placed on the screen.
+struct Glyph {
+  x: u16,
+  y: u16,
+  content: char,
+  style: Style,
+}
+
+impl Glyph {
+  /// Place a glyph at a fractional position, or `None` if it's off the screen.
+  fn new(x: f64, y: f64, content: char, style: Style, width: u16, height: u16) -> Option<Glyph> {
+    let (x, y) = (x.round(), y.round());
+    if x < 0.0 || y < 0.0 || x >= width as f64 || y >= height as f64 {
+      return None;
+    }
+    Some(Glyph {
+      x: x as u16,
+      y: y as u16,
+      content,
+      style,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use ratatui::buffer::Cell;
+
+  use super::*;
+
+  #[test]
+  fn render_survives_stalled_and_negative_speeds() {
+    let area = Rect::new(2, 1, 30, 12);
+    for speed in [0.0, -5.0] {
+      for variance in [0.0, 0.5] {
+        for secs in [0.0, 1.0, 60.0, 3600.0] {
+          let mut buf = Buffer::filled(Rect::new(0, 0, 34, 14), Cell::new("#"));
+          Fireworks::new(Duration::from_secs_f64(secs))
+            .with_launch_speed(RainSpeed::Absolute { speed })
+            .with_launch_speed_variance(variance)
+            .render(area, &mut buf);
+          for position in buf.area.positions().filter(|position| !area.contains(*position)) {
+            assert_eq!(buf[position].symbol(), "#", "drew outside the area at {position:?} with speed {speed}");
+          }
+        }
+      }
+    }
+  }
+}
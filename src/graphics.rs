@@ -0,0 +1,516 @@
+use std::{collections::BTreeMap, fmt::Write, str::FromStr};
+
+use ratatui::style::{Modifier, Style};
+
+use crate::{FallAxes, ParseOptionError};
+
+/// How many bytes of base64 go in each chunk of a kitty graphics escape sequence.
+const KITTY_CHUNK_LEN: usize = 4096;
+
+/// The levels of each channel in the color cube sixel images are quantized to.
+const SIXEL_LEVELS: u8 = 6;
+
+/// A terminal graphics protocol that drops can be drawn in pixels with.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum GraphicsProtocol {
+  /// Sixel images, supported by xterm, foot, mlterm and WezTerm, among others.
+  Sixel,
+
+  /// The kitty graphics protocol, supported by kitty, WezTerm and ghostty.
+  Kitty,
+}
+
+impl GraphicsProtocol {
+  /// Guess the protocol the terminal supports from its environment variables, or
+  /// `None` if it doesn't seem to support any and drops should be drawn in cells.
+  ///
+  /// ```
+  /// use tui_rain_cli::GraphicsProtocol;
+  ///
+  /// let env = |name: &str| (name == "TERM").then(|| "xterm-kitty".to_string());
+  /// assert_eq!(GraphicsProtocol::detect(env), Some(GraphicsProtocol::Kitty));
+  ///
+  /// let env = |name: &str| (name == "TERM").then(|| "xterm-256color".to_string());
+  /// assert_eq!(GraphicsProtocol::detect(env), None);
+  /// ```
+  pub fn detect(env: impl Fn(&str) -> Option<String>) -> Option<GraphicsProtocol> {
+    // Multiplexers swallow graphics unless they are set up to pass them through.
+    if env("TMUX").is_some() || env("STY").is_some() {
+      return None;
+    }
+    let term = env("TERM").unwrap_or_default();
+    let program = env("TERM_PROGRAM").unwrap_or_default();
+    if env("KITTY_WINDOW_ID").is_some() || term == "xterm-kitty" || term == "xterm-ghostty" || program == "WezTerm" || program == "ghostty" {
+      Some(GraphicsProtocol::Kitty)
+    } else if term.contains("sixel") || term.starts_with("foot") || term.starts_with("mlterm") || term.starts_with("contour") {
+      Some(GraphicsProtocol::Sixel)
+    } else {
+      None
+    }
+  }
+
+  /// Encode a canvas as an escape sequence that draws it at the cursor.
+  ///
+  /// Sixel images are drawn over the cells, except where the canvas is transparent.
+  /// Kitty images are drawn below the text and replace the previous image, so they can
+  /// be sent every frame.
+  ///
+  /// ```
+  /// use tui_rain_cli::{Canvas, GraphicsProtocol};
+  ///
+  /// let mut canvas = Canvas::new(1, 1, 2, 6);
+  /// canvas.fill([255, 0, 0, 255]);
+  /// assert_eq!(GraphicsProtocol::Sixel.encode(&canvas), b"\x1bP0;1;0q\"1;1;2;6#180;2;100;0;0#180~~\x1b\\");
+  ///
+  /// let kitty = GraphicsProtocol::Kitty.encode(&canvas);
+  /// assert!(kitty.starts_with(b"\x1b_Ga=T,f=100,i=1,p=1,q=2,C=1,z=-1,c=1,r=1,m=0;"));
+  /// assert!(kitty.ends_with(b"\x1b\\"));
+  /// ```
+  pub fn encode(self, canvas: &Canvas) -> Vec<u8> {
+    match self {
+      GraphicsProtocol::Sixel => sixel(canvas),
+      GraphicsProtocol::Kitty => kitty(canvas),
+    }
+  }
+
+  /// The escape sequence that removes what was drawn, before leaving the terminal.
+  ///
+  /// ```
+  /// use tui_rain_cli::GraphicsProtocol;
+  ///
+  /// assert_eq!(GraphicsProtocol::Kitty.clear(), b"\x1b_Ga=d,d=I,i=1,q=2\x1b\\");
+  /// ```
+  pub fn clear(self) -> &'static [u8] {
+    match self {
+      // Sixel images are part of the cells, so they go with the screen.
+      GraphicsProtocol::Sixel => b"",
+      GraphicsProtocol::Kitty => b"\x1b_Ga=d,d=I,i=1,q=2\x1b\\",
+    }
+  }
+}
+
+impl FromStr for GraphicsProtocol {
+  type Err = ParseOptionError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "sixel" => Ok(GraphicsProtocol::Sixel),
+      "kitty" => Ok(GraphicsProtocol::Kitty),
+      _ => Err(ParseOptionError::new("graphics protocol", s)),
+    }
+  }
+}
+
+/// Pixels covering a grid of cells, that drops are drawn on for terminals with a
+/// [graphics protocol](GraphicsProtocol).
+///
+/// Pixels are RGBA and start out transparent.
+///
+/// ```
+/// use tui_rain_cli::Canvas;
+///
+/// let mut canvas = Canvas::new(80, 24, 10, 20);
+/// assert_eq!((canvas.width(), canvas.height()), (800, 480));
+///
+/// canvas.fill([0, 0, 0, 255]);
+/// canvas.clear_cell(1, 0);
+/// assert_eq!(canvas.pixel(10, 0), [0, 0, 0, 0]);
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Canvas {
+  columns: u16,
+  rows: u16,
+  cell_width: u16,
+  cell_height: u16,
+  pixels: Vec<[u8; 4]>,
+}
+
+impl Canvas {
+  /// Construct a transparent canvas covering `columns` x `rows` cells of the given size
+  /// in pixels.
+  pub fn new(columns: u16, rows: u16, cell_width: u16, cell_height: u16) -> Canvas {
+    Canvas {
+      columns,
+      rows,
+      cell_width,
+      cell_height,
+      pixels: vec![[0; 4]; columns as usize * cell_width as usize * rows as usize * cell_height as usize],
+    }
+  }
+
+  /// The number of cells the canvas covers across.
+  pub fn columns(&self) -> u16 {
+    self.columns
+  }
+
+  /// The number of cells the canvas covers down.
+  pub fn rows(&self) -> u16 {
+    self.rows
+  }
+
+  /// The width in pixels.
+  pub fn width(&self) -> usize {
+    self.columns as usize * self.cell_width as usize
+  }
+
+  /// The height in pixels.
+  pub fn height(&self) -> usize {
+    self.rows as usize * self.cell_height as usize
+  }
+
+  /// Get the RGBA value of a pixel.
+  pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+    self.pixels[y * self.width() + x]
+  }
+
+  /// Set every pixel to an RGBA value.
+  pub fn fill(&mut self, rgba: [u8; 4]) {
+    self.pixels.fill(rgba);
+  }
+
+  /// Make the pixels of a cell transparent, so whatever is in the cell shows through.
+  pub fn clear_cell(&mut self, column: u16, row: u16) {
+    if !self.covers(column, row) {
+      return;
+    }
+    let width = self.width();
+    let (x, y) = (column as usize * self.cell_width as usize, row as usize * self.cell_height as usize);
+    for y in y..y + self.cell_height as usize {
+      self.pixels[y * width + x..y * width + x + self.cell_width as usize].fill([0; 4]);
+    }
+  }
+
+  /// Whether the canvas covers a cell.
+  pub fn covers(&self, column: u16, row: u16) -> bool {
+    column < self.columns && row < self.rows
+  }
+
+  /// Draw a glyph of a drop as a streak through its cell, given the part of the cell it
+  /// spans along its track and where it is across the track, both from 0 to 1.
+  ///
+  /// The streak is as bright as the glyph is bold, and its edges are anti-aliased.
+  pub(crate) fn draw_streak(&mut self, column: u16, row: u16, axes: FallAxes, span: (f64, f64), across: f64, style: Style) {
+    let (cell_width, cell_height) = (self.cell_width as f64, self.cell_height as f64);
+    let (along_size, across_size) = if axes.horizontal {
+      (cell_width, cell_height)
+    } else {
+      (cell_height, cell_width)
+    };
+    let thickness = (across_size / 5.0).max(1.0);
+    let (mut from, mut to) = (span.0 * along_size, span.1 * along_size);
+    // Drops without a tail, like snow flakes, are drawn as dots.
+    if to - from < thickness {
+      from = ((from + to - thickness) / 2.0).clamp(0.0, (along_size - thickness).max(0.0));
+      to = from + thickness;
+    }
+    if axes.reversed {
+      (from, to) = (along_size - to, along_size - from);
+    }
+    let side = (across * across_size - thickness / 2.0).clamp(0.0, (across_size - thickness).max(0.0));
+    let (left, top) = (column as f64 * cell_width, row as f64 * cell_height);
+    let (x, y) = if axes.horizontal {
+      ((from, to), (side, side + thickness))
+    } else {
+      ((side, side + thickness), (from, to))
+    };
+
+    let alpha = if style.add_modifier.contains(Modifier::BOLD) {
+      1.0
+    } else if style.add_modifier.contains(Modifier::DIM) {
+      0.4
+    } else {
+      0.7
+    };
+    let color = style.fg.and_then(crate::rgb).unwrap_or((255, 255, 255));
+    self.blend_rect((left + x.0, top + y.0), (left + x.1, top + y.1), color, alpha);
+  }
+
+  /// Blend a color over a rectangle, weighing each pixel by how much of it is covered.
+  fn blend_rect(&mut self, from: (f64, f64), to: (f64, f64), (r, g, b): (u8, u8, u8), alpha: f64) {
+    let width = self.width();
+    let x_range = from.0.floor().max(0.0) as usize..(to.0.ceil() as usize).min(width);
+    for y in from.1.floor().max(0.0) as usize..(to.1.ceil() as usize).min(self.height()) {
+      let y_coverage = (to.1.min(y as f64 + 1.0) - from.1.max(y as f64)).clamp(0.0, 1.0);
+      for x in x_range.clone() {
+        let x_coverage = (to.0.min(x as f64 + 1.0) - from.0.max(x as f64)).clamp(0.0, 1.0);
+        let alpha = alpha * x_coverage * y_coverage;
+        let pixel = &mut self.pixels[y * width + x];
+        // Composite over whatever is there already.
+        let below = pixel[3] as f64 / 255.0 * (1.0 - alpha);
+        let total = alpha + below;
+        if total <= 0.0 {
+          continue;
+        }
+        let channel = |over: u8, under: u8| ((over as f64 * alpha + under as f64 * below) / total).round() as u8;
+        *pixel = [channel(r, pixel[0]), channel(g, pixel[1]), channel(b, pixel[2]), (total * 255.0).round() as u8];
+      }
+    }
+  }
+}
+
+/// Encode a canvas as a sixel image, quantizing it to a color cube and composing it
+/// over black. Transparent pixels are left out.
+fn sixel(canvas: &Canvas) -> Vec<u8> {
+  let (width, height) = (canvas.width(), canvas.height());
+  let max = (SIXEL_LEVELS - 1) as u32;
+  let quantize = |pixel: [u8; 4]| -> Option<u16> {
+    let level = |channel: u8| (channel as u32 * pixel[3] as u32 * max + 255 * 255 / 2) / (255 * 255);
+    (pixel[3] > 0).then(|| (level(pixel[0]) * (max + 1) * (max + 1) + level(pixel[1]) * (max + 1) + level(pixel[2])) as u16)
+  };
+
+  // P2 = 1 keeps pixels that aren't set transparent.
+  let mut out = format!("\x1bP0;1;0q\"1;1;{width};{height}");
+  let mut defined = vec![false; (max as usize + 1).pow(3)];
+  for band in 0..height.div_ceil(6) {
+    // The sixels of each color in the band, one per column.
+    let mut sixels: BTreeMap<u16, Vec<u8>> = BTreeMap::new();
+    for dy in 0..6.min(height - band * 6) {
+      for x in 0..width {
+        if let Some(color) = quantize(canvas.pixel(x, band * 6 + dy)) {
+          sixels.entry(color).or_insert_with(|| vec![0; width])[x] |= 1 << dy;
+        }
+      }
+    }
+    if band > 0 {
+      out.push('-');
+    }
+    for (i, (color, mut sixels)) in sixels.into_iter().enumerate() {
+      if !std::mem::replace(&mut defined[color as usize], true) {
+        let percent = |level: u16| level as u32 * 100 / max;
+        let levels = max as u16 + 1;
+        let _ = write!(
+          out,
+          "#{color};2;{};{};{}",
+          percent(color / levels / levels),
+          percent(color / levels % levels),
+          percent(color % levels)
+        );
+      }
+      if i > 0 {
+        out.push('$');
+      }
+      let _ = write!(out, "#{color}");
+      while sixels.last() == Some(&0) {
+        sixels.pop();
+      }
+      for run in sixels.chunk_by(|a, b| a == b) {
+        let char = (63 + run[0]) as char;
+        match run.len() {
+          1..=3 => run.iter().for_each(|_| out.push(char)),
+          len => {
+            let _ = write!(out, "!{len}{char}");
+          }
+        }
+      }
+    }
+  }
+  out.push_str("\x1b\\");
+  out.into_bytes()
+}
+
+/// Encode a canvas as a PNG sent with the kitty graphics protocol, placed below the
+/// text and scaled to the cells it covers.
+fn kitty(canvas: &Canvas) -> Vec<u8> {
+  let mut png = Vec::new();
+  {
+    let mut encoder = png::Encoder::new(&mut png, canvas.width() as u32, canvas.height() as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let data: Vec<u8> = canvas.pixels.iter().flatten().copied().collect();
+    if encoder.write_header().and_then(|mut writer| writer.write_image_data(&data)).is_err() {
+      return vec![];
+    }
+  }
+
+  let payload = base64(&png);
+  let chunks: Vec<&[u8]> = payload.as_bytes().chunks(KITTY_CHUNK_LEN).collect();
+  let mut out = Vec::with_capacity(payload.len() + chunks.len() * 16);
+  for (i, chunk) in chunks.iter().enumerate() {
+    let more = (i + 1 < chunks.len()) as u8;
+    match i {
+      0 => out.extend_from_slice(format!("\x1b_Ga=T,f=100,i=1,p=1,q=2,C=1,z=-1,c={},r={},m={more};", canvas.columns, canvas.rows).as_bytes()),
+      _ => out.extend_from_slice(format!("\x1b_Gm={more};").as_bytes()),
+    }
+    out.extend_from_slice(chunk);
+    out.extend_from_slice(b"\x1b\\");
+  }
+  out
+}
+
+/// Encode bytes as standard base64 with padding.
+fn base64(bytes: &[u8]) -> String {
+  const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+  let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+  for chunk in bytes.chunks(3) {
+    let n = (chunk[0] as u32) << 16 | (*chunk.get(1).unwrap_or(&0) as u32) << 8 | *chunk.get(2).unwrap_or(&0) as u32;
+    for i in 0..4 {
+      match i <= chunk.len() {
+        true => out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char),
+        false => out.push('='),
+      }
+    }
+  }
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use ratatui::style::Color;
+
+  use super::*;
+
+  /// Falling down, with straight tracks.
+  const DOWN: FallAxes = FallAxes {
+    along: 1,
+    across: 1,
+    horizontal: false,
+    reversed: false,
+    slant: 0.0,
+  };
+
+  /// Decode standard base64 with padding.
+  fn unbase64(text: &[u8]) -> Vec<u8> {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let sextets: Vec<u32> = text
+      .iter()
+      .filter(|&&c| c != b'=')
+      .map(|c| ALPHABET.iter().position(|a| a == c).unwrap() as u32)
+      .collect();
+    sextets
+      .chunks(4)
+      .flat_map(|chunk| {
+        let n = chunk.iter().enumerate().fold(0, |n, (i, sextet)| n | sextet << (18 - 6 * i));
+        (0..chunk.len() - 1).map(move |i| (n >> (16 - 8 * i)) as u8)
+      })
+      .collect()
+  }
+
+  /// Split a kitty escape sequence into the control data and payload of its chunks.
+  fn kitty_chunks(bytes: &[u8]) -> Vec<(String, &[u8])> {
+    let text = std::str::from_utf8(bytes).unwrap();
+    text
+      .split_terminator("\x1b\\")
+      .map(|chunk| {
+        let (control, payload) = chunk.strip_prefix("\x1b_G").unwrap().split_once(';').unwrap();
+        (control.to_string(), payload.as_bytes())
+      })
+      .collect()
+  }
+
+  /// Decode the PNG sent in kitty chunks to its RGBA pixels.
+  fn kitty_pixels(chunks: &[(String, &[u8])]) -> Vec<u8> {
+    let payload: Vec<u8> = chunks.iter().flat_map(|(_, payload)| payload.iter().copied()).collect();
+    let png = unbase64(&payload);
+    let mut reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    reader.next_frame(&mut pixels).unwrap();
+    pixels
+  }
+
+  #[test]
+  fn base64_matches_known_vectors() {
+    // The test vectors of RFC 4648.
+    for (bytes, encoded) in [
+      ("", ""),
+      ("f", "Zg=="),
+      ("fo", "Zm8="),
+      ("foo", "Zm9v"),
+      ("foob", "Zm9vYg=="),
+      ("fooba", "Zm9vYmE="),
+      ("foobar", "Zm9vYmFy"),
+    ] {
+      assert_eq!(base64(bytes.as_bytes()), encoded);
+      assert_eq!(unbase64(encoded.as_bytes()), bytes.as_bytes());
+    }
+    assert_eq!(base64(&[0xfb, 0xff, 0xbf]), "+/+/");
+  }
+
+  #[test]
+  fn blend_rect_weighs_pixels_by_coverage() {
+    let mut canvas = Canvas::new(1, 1, 4, 2);
+    canvas.blend_rect((0.5, 0.0), (2.0, 1.0), (255, 0, 0), 1.0);
+    assert_eq!(canvas.pixel(0, 0), [255, 0, 0, 128]);
+    assert_eq!(canvas.pixel(1, 0), [255, 0, 0, 255]);
+    assert_eq!(canvas.pixel(2, 0), [0; 4]);
+    assert_eq!(canvas.pixel(1, 1), [0; 4]);
+
+    // Blending over a pixel mixes the colors by their share of the total.
+    canvas.blend_rect((0.0, 0.0), (1.0, 1.0), (0, 0, 255), 0.5);
+    assert_eq!(canvas.pixel(0, 0), [85, 0, 170, 192]);
+  }
+
+  #[test]
+  fn draw_streak_fills_its_part_of_the_cell() {
+    let mut canvas = Canvas::new(2, 2, 2, 6);
+    let bold = Style::default().fg(Color::Rgb(255, 0, 0)).add_modifier(Modifier::BOLD);
+    canvas.draw_streak(1, 1, DOWN, (0.0, 1.0), 0.75, bold);
+    for y in 0..canvas.height() {
+      for x in 0..canvas.width() {
+        let expected = if x == 3 && y >= 6 { [255, 0, 0, 255] } else { [0; 4] };
+        assert_eq!(canvas.pixel(x, y), expected, "pixel ({x}, {y})");
+      }
+    }
+
+    // Across the track instead, a dim streak covering the top half of the cell.
+    let mut canvas = Canvas::new(1, 1, 6, 2);
+    let right = FallAxes { horizontal: true, ..DOWN };
+    let dim = Style::default().fg(Color::Rgb(0, 255, 0)).add_modifier(Modifier::DIM);
+    canvas.draw_streak(0, 0, right, (0.5, 1.0), 0.0, dim);
+    let row = |y| (0..6).map(|x| canvas.pixel(x, y)).collect::<Vec<_>>();
+    assert_eq!(row(0), [[0; 4], [0; 4], [0; 4], [0, 255, 0, 102], [0, 255, 0, 102], [0, 255, 0, 102]]);
+    assert_eq!(row(1), [[0; 4]; 6]);
+  }
+
+  #[test]
+  fn sixel_encodes_a_streak_over_black() {
+    let mut canvas = Canvas::new(2, 2, 2, 6);
+    canvas.fill([0, 0, 0, 255]);
+    let bold = Style::default().fg(Color::Rgb(255, 0, 0)).add_modifier(Modifier::BOLD);
+    canvas.draw_streak(1, 1, DOWN, (0.0, 1.0), 0.75, bold);
+    canvas.clear_cell(0, 0);
+    assert_eq!(
+      String::from_utf8(GraphicsProtocol::Sixel.encode(&canvas)).unwrap(),
+      concat!(
+        "\x1bP0;1;0q\"1;1;4;12",
+        // The first band is black, except for the cleared cell.
+        "#0;2;0;0;0#0??~~",
+        // The second band has the streak, in a color defined on first use.
+        "-#0~~~#180;2;100;0;0$#180???~",
+        "\x1b\\",
+      )
+    );
+  }
+
+  #[test]
+  fn kitty_sends_the_canvas_as_a_png() {
+    let mut canvas = Canvas::new(2, 2, 2, 6);
+    let bold = Style::default().fg(Color::Rgb(255, 0, 0)).add_modifier(Modifier::BOLD);
+    canvas.draw_streak(1, 1, DOWN, (0.0, 1.0), 0.75, bold);
+    let bytes = GraphicsProtocol::Kitty.encode(&canvas);
+    let chunks = kitty_chunks(&bytes);
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0].0, "a=T,f=100,i=1,p=1,q=2,C=1,z=-1,c=2,r=2,m=0");
+    assert_eq!(kitty_pixels(&chunks), canvas.pixels.iter().flatten().copied().collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn kitty_splits_large_images_into_chunks() {
+    // Noise doesn't compress, so the PNG takes several chunks.
+    let mut canvas = Canvas::new(16, 8, 8, 16);
+    let mut seed = 1u32;
+    for pixel in canvas.pixels.iter_mut() {
+      seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+      *pixel = seed.to_le_bytes();
+    }
+    let bytes = GraphicsProtocol::Kitty.encode(&canvas);
+    let chunks = kitty_chunks(&bytes);
+    assert!(chunks.len() > 2);
+    assert_eq!(chunks[0].0, "a=T,f=100,i=1,p=1,q=2,C=1,z=-1,c=16,r=8,m=1");
+    for (control, payload) in &chunks[1..chunks.len() - 1] {
+      assert_eq!(control, "m=1");
+      assert_eq!(payload.len(), KITTY_CHUNK_LEN);
+    }
+    assert_eq!(chunks.last().unwrap().0, "m=0");
+    assert_eq!(kitty_pixels(&chunks), canvas.pixels.iter().flatten().copied().collect::<Vec<_>>());
+  }
+}
@@ -1,6 +1,6 @@
 #![doc = include_str!("../README.md")]
 
-use std::{cmp::Ordering, time::Duration};
+use std::{cmp::Ordering, error::Error, f64::consts::TAU, fmt, str::FromStr, sync::Arc, time::Duration};
 
 use rand::{RngCore, SeedableRng};
 use rand_pcg::Pcg64Mcg;
@@ -8,8 +8,30 @@ use ratatui::{
   buffer::Buffer,
   layout::Rect,
   style::{Color, Style, Stylize},
-  widgets::Widget,
+  text::{StyledGrapheme, Text},
+  widgets::{Block, BorderType, Padding, Widget},
 };
+use unicode_width::UnicodeWidthStr;
+
+mod capabilities;
+mod effect;
+mod fire;
+mod fireworks;
+mod graphics;
+mod starfield;
+
+pub use capabilities::{Capabilities, ColorSupport};
+pub use effect::{Effect, EffectConfig, EffectRegistry};
+pub use fire::Fire;
+pub use fireworks::Fireworks;
+pub use graphics::{Canvas, GraphicsProtocol};
+pub use starfield::Starfield;
+
+/// How many times per second hidden characters of a decoding message change.
+const DECODE_FLICKER_RATE: f64 = 15.0;
+
+/// How long a character flashes in the head color after being decoded, in seconds.
+const DECODE_FLASH_SECS: f64 = 0.3;
 
 /// A configuration for the density of the rain effect.
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
@@ -38,7 +60,7 @@ impl RainDensity {
     match self {
       RainDensity::Absolute { num_drops } => *num_drops,
       RainDensity::Relative { sparseness } if *sparseness == 0 => 0,
-      RainDensity::Relative { sparseness } => (area.width * area.height) as usize / *sparseness,
+      RainDensity::Relative { sparseness } => area.width as usize * area.height as usize / *sparseness,
       RainDensity::Dense => RainDensity::Relative { sparseness: 20 }.num_drops(area),
       RainDensity::Normal => RainDensity::Relative { sparseness: 50 }.num_drops(area),
       RainDensity::Sparse => RainDensity::Relative { sparseness: 100 }.num_drops(area),
@@ -63,8 +85,14 @@ pub enum RainSpeed {
 }
 
 impl RainSpeed {
-  /// Get the absolute speed.
-  fn speed(&self) -> f64 {
+  /// Get the absolute speed in pixels / second.
+  ///
+  /// ```
+  /// use tui_rain_cli::RainSpeed;
+  ///
+  /// assert_eq!(RainSpeed::Fast.speed(), 20.0);
+  /// ```
+  pub fn speed(&self) -> f64 {
     match self {
       RainSpeed::Absolute { speed } => *speed,
       RainSpeed::Fast => 20.0,
@@ -72,236 +100,1739 @@ impl RainSpeed {
       RainSpeed::Slow => 5.0,
     }
   }
-}
-
-/// A character set for the rain.
-#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
-pub enum CharacterSet {
-  /// An explicit enumeration of character options. This is the least performant.
-  Explicit { options: Vec<char> },
-
-  /// A range of unicode values.
-  UnicodeRange { start: u32, len: u32 },
+}
+
+/// A character set for the rain.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum CharacterSet {
+  /// An explicit enumeration of character options. This is the least performant.
+  Explicit { options: Vec<char> },
+
+  /// A range of unicode values.
+  UnicodeRange { start: u32, len: u32 },
+
+  /// Half-width Japanese Kana characters. This is the closest to the original.
+  ///
+  /// Equivalent to `CharacterSet::UnicodeRange { start: 0xFF66, len: 56 }`.
+  HalfKana,
+
+  /// The lowercase English alphabet.
+  ///
+  /// Equivalent to `CharacterSet::UnicodeRange { start: 0x61, len: 26 }`.
+  Lowercase,
+
+  /// An ordered sequence of characters, e.g. text piped in from another program.
+  ///
+  /// Instead of randomly cycling, each drop spells out consecutive characters of the
+  /// sequence along its track, so the text is displayed in order as the drop falls.
+  /// The characters are shared, so the set is cheap to clone every frame.
+  Sequence { chars: Arc<[char]> },
+}
+
+impl CharacterSet {
+  fn get(&self, seed: u32) -> char {
+    match self {
+      CharacterSet::Explicit { options } => options[seed as usize % options.len()],
+      CharacterSet::UnicodeRange { start, len } => char::from_u32((seed % len) + start).unwrap(),
+      CharacterSet::HalfKana => CharacterSet::UnicodeRange { start: 0xFF66, len: 56 }.get(seed),
+      CharacterSet::Lowercase => CharacterSet::UnicodeRange { start: 0x61, len: 26 }.get(seed),
+      CharacterSet::Sequence { chars } if chars.is_empty() => ' ',
+      CharacterSet::Sequence { chars } => chars[seed as usize % chars.len()],
+    }
+  }
+
+  fn size(&self) -> usize {
+    match self {
+      CharacterSet::Explicit { options } => options.len(),
+      CharacterSet::UnicodeRange { start: _, len } => *len as usize,
+      CharacterSet::HalfKana => 56,
+      CharacterSet::Lowercase => 26,
+      CharacterSet::Sequence { chars } => chars.len(),
+    }
+  }
+
+  /// An ASCII stand-in for terminals without unicode, or `None` if the set is ASCII
+  /// already or is text to be shown as is.
+  fn ascii(&self) -> Option<CharacterSet> {
+    // Particles like emoji and petals become simple shapes.
+    let shapes = CharacterSet::Explicit {
+      options: vec!['*', '+', 'o', '.'],
+    };
+    match self {
+      CharacterSet::HalfKana => Some(CharacterSet::UnicodeRange { start: 0x21, len: 94 }),
+      CharacterSet::UnicodeRange { start, len } if start + len > 0x80 => Some(shapes),
+      CharacterSet::Explicit { options } if !options.iter().all(char::is_ascii) => {
+        let options: Vec<char> = options.iter().copied().filter(char::is_ascii).collect();
+        Some(if options.is_empty() { shapes } else { CharacterSet::Explicit { options } })
+      }
+      _ => None,
+    }
+  }
+}
+
+/// How a message is animated onto the screen.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum MessageEffect {
+  /// The message drops in from the top of the screen and settles in place.
+  DropIn,
+
+  /// The message is shown in place straight away.
+  Static,
+
+  /// The message is shown in place and blinks once per second.
+  Blink,
+
+  /// The message is shown in place, but each character is hidden behind flickering
+  /// rain glyphs until a drop head passes over it and decodes it.
+  Decode,
+}
+
+/// Where the message is placed on the screen.
+///
+/// Placement is relative to the screen area inside the [message padding](Rain::with_message_padding).
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum MessagePosition {
+  /// Centered on the screen.
+  Center,
+
+  /// Centered at the top of the screen.
+  Top,
+
+  /// Centered at the bottom of the screen.
+  Bottom,
+
+  /// Centered on the left side of the screen.
+  Left,
+
+  /// Centered on the right side of the screen.
+  Right,
+
+  /// In the top left corner.
+  TopLeft,
+
+  /// In the top right corner.
+  TopRight,
+
+  /// In the bottom left corner.
+  BottomLeft,
+
+  /// In the bottom right corner.
+  BottomRight,
+
+  /// At a percentage of the free space on each axis.
+  ///
+  /// `0` places the message flush against the left / top edge, `100` flush against the
+  /// right / bottom edge. `Percent { x: 50, y: 50 }` is equivalent to `Center`.
+  Percent { x: u16, y: u16 },
+}
+
+impl MessagePosition {
+  /// Get the horizontal and vertical percentages of the free space.
+  fn percentages(&self) -> (u16, u16) {
+    match self {
+      MessagePosition::Center => (50, 50),
+      MessagePosition::Top => (50, 0),
+      MessagePosition::Bottom => (50, 100),
+      MessagePosition::Left => (0, 50),
+      MessagePosition::Right => (100, 50),
+      MessagePosition::TopLeft => (0, 0),
+      MessagePosition::TopRight => (100, 0),
+      MessagePosition::BottomLeft => (0, 100),
+      MessagePosition::BottomRight => (100, 100),
+      MessagePosition::Percent { x, y } => ((*x).min(100), (*y).min(100)),
+    }
+  }
+}
+
+impl FromStr for MessagePosition {
+  type Err = ParseOptionError;
+
+  /// Parse a named position such as `top-left`, or percentages such as `80%,10%`.
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let position = match s.to_lowercase().replace(['-', '_', ' '], "").as_str() {
+      "center" => MessagePosition::Center,
+      "top" => MessagePosition::Top,
+      "bottom" => MessagePosition::Bottom,
+      "left" => MessagePosition::Left,
+      "right" => MessagePosition::Right,
+      "topleft" => MessagePosition::TopLeft,
+      "topright" => MessagePosition::TopRight,
+      "bottomleft" => MessagePosition::BottomLeft,
+      "bottomright" => MessagePosition::BottomRight,
+      percentages => {
+        let parse = |value: &str| value.trim_end_matches('%').parse::<u16>().ok().filter(|value| *value <= 100);
+        match percentages.split_once(',').map(|(x, y)| (parse(x), parse(y))) {
+          Some((Some(x), Some(y))) => MessagePosition::Percent { x, y },
+          _ => return Err(ParseOptionError::new("message position", s)),
+        }
+      }
+    };
+    Ok(position)
+  }
+}
+
+/// How the lines of a multi-line message are aligned with each other.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum MessageAlignment {
+  /// Lines start at the left edge of the message.
+  Left,
+
+  /// Lines are centered on each other.
+  Center,
+
+  /// Lines end at the right edge of the message.
+  Right,
+}
+
+impl FromStr for MessageAlignment {
+  type Err = ParseOptionError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "left" => Ok(MessageAlignment::Left),
+      "center" => Ok(MessageAlignment::Center),
+      "right" => Ok(MessageAlignment::Right),
+      _ => Err(ParseOptionError::new("message alignment", s)),
+    }
+  }
+}
+
+/// The screen edge a [dropping in](MessageEffect::DropIn) message enters from.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum MessageEdge {
+  /// Fall down from above the screen.
+  Top,
+
+  /// Rise up from below the screen.
+  Bottom,
+
+  /// Slide in from the left of the screen.
+  Left,
+
+  /// Slide in from the right of the screen.
+  Right,
+}
+
+impl FromStr for MessageEdge {
+  type Err = ParseOptionError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "top" => Ok(MessageEdge::Top),
+      "bottom" => Ok(MessageEdge::Bottom),
+      "left" => Ok(MessageEdge::Left),
+      "right" => Ok(MessageEdge::Right),
+      _ => Err(ParseOptionError::new("message edge", s)),
+    }
+  }
+}
+
+/// A backdrop drawn behind the message to keep it readable over the rain.
+///
+/// The backdrop covers the message and its [margin](Rain::with_message_backdrop_margin).
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum MessageBackdrop {
+  /// No backdrop. Rain shows between the characters of the message.
+  None,
+
+  /// Keep the area around the message clear of rain.
+  Clear,
+
+  /// Keep the rain around the message, but dim it.
+  Dim,
+
+  /// Fill the area around the message with a background color.
+  Solid { color: Color },
+
+  /// Keep the area around the message clear of rain and draw a border around it, in
+  /// the color of the message.
+  Border { border_type: BorderType },
+}
+
+impl FromStr for MessageBackdrop {
+  type Err = ParseOptionError;
+
+  /// Parse `none`, `clear`, `dim`, `solid:<color>` or `border[:plain|rounded|double|thick]`.
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let (kind, argument) = s.split_once(':').map_or((s, None), |(kind, argument)| (kind, Some(argument)));
+    let backdrop = match (kind.to_lowercase().as_str(), argument) {
+      ("none", None) => Some(MessageBackdrop::None),
+      ("clear", None) => Some(MessageBackdrop::Clear),
+      ("dim", None) => Some(MessageBackdrop::Dim),
+      ("solid", Some(color)) => color.parse().ok().map(|color| MessageBackdrop::Solid { color }),
+      ("border", None) => Some(MessageBackdrop::Border {
+        border_type: BorderType::Plain,
+      }),
+      ("border", Some(border_type)) => match border_type.to_lowercase().as_str() {
+        "plain" => Some(BorderType::Plain),
+        "rounded" => Some(BorderType::Rounded),
+        "double" => Some(BorderType::Double),
+        "thick" => Some(BorderType::Thick),
+        _ => None,
+      }
+      .map(|border_type| MessageBackdrop::Border { border_type }),
+      _ => None,
+    };
+    backdrop.ok_or_else(|| ParseOptionError::new("message backdrop", s))
+  }
+}
+
+/// How drops interact with the [message backdrop](Rain::with_message_backdrop).
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum BackdropDrops {
+  /// Drops pass behind the backdrop and reappear below it.
+  PassBehind,
+
+  /// Drops stop when they reach the backdrop, leaving the area below it dry.
+  Stop,
+}
+
+impl FromStr for BackdropDrops {
+  type Err = ParseOptionError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().replace(['-', '_'], "").as_str() {
+      "passbehind" => Ok(BackdropDrops::PassBehind),
+      "stop" => Ok(BackdropDrops::Stop),
+      _ => Err(ParseOptionError::new("backdrop drops", s)),
+    }
+  }
+}
+
+/// How a [mask](Rain::with_mask) affects the rain.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum MaskMode {
+  /// Glyphs only appear where the mask is set, so the rain takes its shape.
+  Clip,
+
+  /// Glyphs only appear where the mask isn't set, leaving its shape dry.
+  Cutout,
+
+  /// Glyphs passing over the mask take its color, so it shows through the rain. Drop
+  /// heads keep their color.
+  Tint,
+}
+
+impl FromStr for MaskMode {
+  type Err = ParseOptionError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "clip" => Ok(MaskMode::Clip),
+      "cutout" => Ok(MaskMode::Cutout),
+      "tint" => Ok(MaskMode::Tint),
+      _ => Err(ParseOptionError::new("mask mode", s)),
+    }
+  }
+}
+
+/// The direction drops fall in.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FallDirection {
+  /// Drops fall from the top to the bottom, like rain.
+  Down,
+
+  /// Drops rise from the bottom to the top, like bubbles or embers.
+  Up,
+
+  /// Drops stream from the right to the left.
+  Left,
+
+  /// Drops stream from the left to the right.
+  Right,
+
+  /// Drops fall at an angle, in degrees clockwise from straight down, e.g. `20` for
+  /// rain blown to the right, `90` for right and `180` for up.
+  Angle { degrees: f64 },
+}
+
+impl FallDirection {
+  /// Get the direction in degrees clockwise from straight down, from 0 to 360.
+  fn degrees(&self) -> f64 {
+    match self {
+      FallDirection::Down => 0.0,
+      FallDirection::Right => 90.0,
+      FallDirection::Up => 180.0,
+      FallDirection::Left => 270.0,
+      FallDirection::Angle { degrees } => degrees.rem_euclid(360.0),
+    }
+  }
+
+  /// Get the axes tracks run along in an area, for the axis closest to the direction.
+  fn axes(&self, width: u16, height: u16) -> FallAxes {
+    let (sin, cos) = self.degrees().to_radians().sin_cos();
+    if cos.abs() >= sin.abs() {
+      FallAxes {
+        along: height,
+        across: width,
+        horizontal: false,
+        reversed: cos < 0.0,
+        slant: sin / cos.abs(),
+      }
+    } else {
+      FallAxes {
+        along: width,
+        across: height,
+        horizontal: true,
+        reversed: sin < 0.0,
+        slant: cos / sin.abs(),
+      }
+    }
+  }
+}
+
+impl FromStr for FallDirection {
+  type Err = ParseOptionError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "down" => Ok(FallDirection::Down),
+      "up" => Ok(FallDirection::Up),
+      "left" => Ok(FallDirection::Left),
+      "right" => Ok(FallDirection::Right),
+      degrees => match degrees.parse::<f64>() {
+        Ok(degrees) if degrees.is_finite() => Ok(FallDirection::Angle { degrees }),
+        _ => Err(ParseOptionError::new("fall direction", s)),
+      },
+    }
+  }
+}
+
+/// A distribution that a parameter of each drop is drawn from.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum Distribution {
+  /// Evenly spread within ± the variance.
+  #[default]
+  Uniform,
+
+  /// Clustered around the target, with the same standard deviation as uniform.
+  Normal,
+
+  /// Mostly a bit below the target, with a long tail of much higher values.
+  LogNormal,
+
+  /// Clustered around two modes, at ± the variance.
+  Bimodal,
+}
+
+impl Distribution {
+  /// Map a quantile between 0 and 1 to a factor around 1, spread by `variance`.
+  ///
+  /// The factor grows with the quantile, so drawing several parameters from the same
+  /// quantile correlates them.
+  fn sample(&self, quantile: f64, variance: f64) -> f64 {
+    match self {
+      Distribution::Uniform => 1.0 + variance * (2.0 * quantile - 1.0),
+      Distribution::Normal => 1.0 + variance * probit(quantile) / 3f64.sqrt(),
+      Distribution::LogNormal => (variance * probit(quantile) / 3f64.sqrt()).exp(),
+      Distribution::Bimodal if quantile < 0.5 => 1.0 - variance * (1.0 - 0.2 * probit(2.0 * quantile)),
+      Distribution::Bimodal => 1.0 + variance * (1.0 + 0.2 * probit(2.0 * quantile - 1.0)),
+    }
+  }
+}
+
+impl FromStr for Distribution {
+  type Err = ParseOptionError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "uniform" => Ok(Distribution::Uniform),
+      "normal" => Ok(Distribution::Normal),
+      "log-normal" | "lognormal" => Ok(Distribution::LogNormal),
+      "bimodal" => Ok(Distribution::Bimodal),
+      _ => Err(ParseOptionError::new("distribution", s)),
+    }
+  }
+}
+
+/// How a parameter varies from drop to drop.
+///
+/// Parses from `<distribution>[:<variance>]`, e.g. `normal:0.3`.
+///
+/// ```
+/// use tui_rain_cli::{Distribution, Variation};
+///
+/// let variation: Variation = "log-normal:0.5".parse().unwrap();
+/// assert_eq!(variation, Variation { distribution: Distribution::LogNormal, variance: 0.5 });
+/// ```
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct Variation {
+  pub distribution: Distribution,
+  /// How far values spread, as a fraction of the target.
+  pub variance: f64,
+}
+
+impl Variation {
+  /// Map a quantile between 0 and 1 to a factor around 1.
+  fn sample(&self, quantile: f64) -> f64 {
+    self.distribution.sample(quantile, self.variance)
+  }
+}
+
+impl FromStr for Variation {
+  type Err = ParseOptionError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let (distribution, variance) = s.split_once(':').unwrap_or((s, "0.3"));
+    Ok(Variation {
+      distribution: distribution.parse().map_err(|_| ParseOptionError::new("variation", s))?,
+      variance: variance
+        .parse()
+        .ok()
+        .filter(|variance: &f64| variance.is_finite())
+        .ok_or_else(|| ParseOptionError::new("variation", s))?,
+    })
+  }
+}
+
+/// An error returned when parsing a configuration option from a string fails.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ParseOptionError {
+  option: &'static str,
+  value: String,
+}
+
+impl ParseOptionError {
+  fn new(option: &'static str, value: &str) -> ParseOptionError {
+    ParseOptionError {
+      option,
+      value: value.to_string(),
+    }
+  }
+}
+
+impl fmt::Display for ParseOptionError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "invalid {} `{}`", self.option, self.value)
+  }
+}
+
+impl Error for ParseOptionError {}
+
+/// An easing curve, shaping how an [animated](Animated) value moves from one keyframe
+/// to the next.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Easing {
+  /// Move at a constant rate.
+  Linear,
+
+  /// Hold the previous value, then switch when the keyframe is reached.
+  Step,
+
+  /// Start slowly and speed up.
+  EaseIn,
+
+  /// Start quickly and slow down.
+  EaseOut,
+
+  /// Start and end slowly.
+  EaseInOut,
+}
+
+impl Easing {
+  /// Map linear progress in [0, 1] to eased progress in [0, 1].
+  fn apply(&self, t: f64) -> f64 {
+    match self {
+      Easing::Linear => t,
+      Easing::Step if t < 1.0 => 0.0,
+      Easing::Step => 1.0,
+      Easing::EaseIn => t * t,
+      Easing::EaseOut => t * (2.0 - t),
+      Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+    }
+  }
+
+  /// The integral of the eased progress from 0 to `t`.
+  fn integral(&self, t: f64) -> f64 {
+    match self {
+      Easing::Linear => t * t / 2.0,
+      Easing::Step => 0.0,
+      Easing::EaseIn => t * t * t / 3.0,
+      Easing::EaseOut => t * t - t * t * t / 3.0,
+      Easing::EaseInOut => t * t * t - t * t * t * t / 2.0,
+    }
+  }
+}
+
+/// A value that can be blended with another of the same type.
+pub trait Interpolate: Clone {
+  /// Blend from `self` (at 0) to `other` (at 1).
+  fn interpolate(&self, other: &Self, t: f64) -> Self;
+}
+
+impl Interpolate for f64 {
+  fn interpolate(&self, other: &f64, t: f64) -> f64 {
+    self + (other - self) * t
+  }
+}
+
+impl Interpolate for Duration {
+  fn interpolate(&self, other: &Duration, t: f64) -> Duration {
+    Duration::from_secs_f64(self.as_secs_f64().interpolate(&other.as_secs_f64(), t).max(0.0))
+  }
+}
+
+impl Interpolate for RainSpeed {
+  fn interpolate(&self, other: &RainSpeed, t: f64) -> RainSpeed {
+    RainSpeed::Absolute {
+      speed: self.speed().interpolate(&other.speed(), t),
+    }
+  }
+}
+
+impl Interpolate for Color {
+  /// Blend in RGB, using the common xterm values for named colors. Colors without an
+  /// RGB value, like indexed colors, switch halfway instead.
+  fn interpolate(&self, other: &Color, t: f64) -> Color {
+    match (rgb(*self), rgb(*other)) {
+      _ if self == other => *self,
+      (Some(from), Some(to)) => {
+        let channel = |from: u8, to: u8| (from as f64).interpolate(&(to as f64), t).round() as u8;
+        Color::Rgb(channel(from.0, to.0), channel(from.1, to.1), channel(from.2, to.2))
+      }
+      _ if t < 0.5 => *self,
+      _ => *other,
+    }
+  }
+}
+
+/// A keyframe of an [animated](Animated) value.
+#[derive(Clone, PartialEq, Debug)]
+struct Keyframe<T> {
+  at: Duration,
+  value: T,
+  /// How the value eases in from the previous keyframe.
+  easing: Easing,
+}
+
+/// A parameter that changes over time, following keyframes.
+///
+/// Anything that accepts an animated value also accepts a constant one. For example,
+/// to ramp the rain from a drizzle to a downpour and back over five minutes:
+///
+/// ```
+/// use std::time::Duration;
+/// use tui_rain_cli::{Animated, Easing, Rain, RainDensity};
+///
+/// let elapsed = Duration::from_secs(5);
+///
+/// Rain::new_rain(elapsed)
+///     .with_rain_density(
+///         Animated::new(RainDensity::Sparse)
+///             .then(Duration::from_secs(150), RainDensity::Dense, Easing::EaseInOut)
+///             .then(Duration::from_secs(150), RainDensity::Sparse, Easing::EaseInOut)
+///             .with_repeat(true),
+///     );
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct Animated<T> {
+  keyframes: Vec<Keyframe<T>>,
+  repeat: bool,
+}
+
+impl<T> Animated<T> {
+  /// Construct an animation starting at `value`.
+  pub fn new(value: T) -> Animated<T> {
+    Animated {
+      keyframes: vec![Keyframe {
+        at: Duration::ZERO,
+        value,
+        easing: Easing::Linear,
+      }],
+      repeat: false,
+    }
+  }
+
+  /// Add a keyframe reaching `value` a duration `after` the previous keyframe.
+  pub fn then(mut self, after: Duration, value: T, easing: Easing) -> Animated<T> {
+    let at = self.keyframes.last().map_or(Duration::ZERO, |keyframe| keyframe.at) + after;
+    self.keyframes.push(Keyframe { at, value, easing });
+    self
+  }
+
+  /// Set whether the animation starts over after the last keyframe, instead of
+  /// holding its value.
+  pub fn with_repeat(mut self, repeat: bool) -> Animated<T> {
+    self.repeat = repeat;
+    self
+  }
+
+  /// Whether the value never changes.
+  fn is_constant(&self) -> bool {
+    self.keyframes.len() == 1
+  }
+
+  /// How long one run through the keyframes takes.
+  fn total(&self) -> Duration {
+    self.keyframes.last().map_or(Duration::ZERO, |keyframe| keyframe.at)
+  }
+
+  /// Find the keyframes around `elapsed`, and the eased progress between them.
+  fn segment(&self, elapsed: Duration) -> (&T, &T, f64) {
+    let total = self.total();
+    let elapsed = match self.repeat && !total.is_zero() {
+      true => Duration::from_nanos((elapsed.as_nanos() % total.as_nanos()) as u64),
+      false => elapsed,
+    };
+    let next = self.keyframes.iter().position(|keyframe| keyframe.at > elapsed);
+    match next {
+      Some(next) if next > 0 => {
+        let (from, to) = (&self.keyframes[next - 1], &self.keyframes[next]);
+        let progress = (elapsed - from.at).as_secs_f64() / (to.at - from.at).as_secs_f64();
+        (&from.value, &to.value, to.easing.apply(progress))
+      }
+      _ => {
+        let last = &self.keyframes[self.keyframes.len() - 1].value;
+        (last, last, 0.0)
+      }
+    }
+  }
+
+  /// Integrate a numeric view of the value from the start of the animation to `elapsed`,
+  /// e.g. how far the rain has fallen at an animated speed.
+  ///
+  /// The numeric value is assumed to blend linearly, so each easing curve can be
+  /// integrated in closed form.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{Animated, Easing};
+  ///
+  /// // Two seconds ramping from 2 to 4, then a second at 4.
+  /// let speed = Animated::new(2.0).then(Duration::from_secs(2), 4.0, Easing::Linear);
+  /// assert_eq!(speed.integrate(Duration::from_secs(3), |speed| *speed), 10.0);
+  /// ```
+  pub fn integrate(&self, elapsed: Duration, f: impl Fn(&T) -> f64) -> f64 {
+    let total = self.total().as_secs_f64();
+    let elapsed = elapsed.as_secs_f64();
+    let (repeats, elapsed) = match self.repeat && total > 0.0 {
+      true => ((elapsed / total).floor(), elapsed % total),
+      false => (0.0, elapsed),
+    };
+    let integrate_until = |until: f64| {
+      let mut sum = 0.0;
+      for pair in self.keyframes.windows(2) {
+        let (from, to) = (&pair[0], &pair[1]);
+        let (start, end) = (from.at.as_secs_f64(), to.at.as_secs_f64());
+        if until <= start {
+          return sum;
+        }
+        let length = end - start;
+        let progress = ((until - start) / length).min(1.0);
+        sum += length * (f(&from.value) * progress + (f(&to.value) - f(&from.value)) * to.easing.integral(progress));
+      }
+      let last = self.keyframes.last().map_or(0.0, |keyframe| keyframe.at.as_secs_f64());
+      sum + f(&self.keyframes[self.keyframes.len() - 1].value) * (until - last).max(0.0)
+    };
+    let once = if repeats > 0.0 { integrate_until(total) } else { 0.0 };
+    once * repeats + integrate_until(elapsed)
+  }
+
+  /// Find how long it takes for the [integral](Animated::integrate) of a numeric view of
+  /// the value to reach `integral`, assuming the view is never negative.
+  ///
+  /// Only the keyframe segment where the integral is reached is solved: in closed form
+  /// for linear and step easing, and with Newton's method for the eased curves.
+  fn time_to_reach(&self, integral: f64, f: impl Fn(&T) -> f64) -> f64 {
+    let total = self.total().as_secs_f64();
+    let (mut time, mut remaining) = (0.0, integral.max(0.0));
+    if self.repeat && total > 0.0 {
+      let once = self.integrate(self.total(), &f);
+      if once > 0.0 {
+        let repeats = (remaining / once).floor();
+        time = repeats * total;
+        remaining -= repeats * once;
+      }
+    }
+    for pair in self.keyframes.windows(2) {
+      let (from, to) = (&pair[0], &pair[1]);
+      let length = (to.at - from.at).as_secs_f64();
+      let (start, change) = (f(&from.value), f(&to.value) - f(&from.value));
+      let segment = length * (start + change * to.easing.integral(1.0));
+      if remaining > segment {
+        remaining -= segment;
+        continue;
+      }
+      // Solve `start * p + change * integral(p) = target` for the progress `p`.
+      let target = remaining / length.max(f64::MIN_POSITIVE);
+      let progress = match to.easing {
+        Easing::Step => target / start.max(f64::MIN_POSITIVE),
+        Easing::Linear if change.abs() < 1e-12 => target / start.max(f64::MIN_POSITIVE),
+        Easing::Linear => ((start * start + 2.0 * change * target).max(0.0).sqrt() - start) / change,
+        easing => {
+          let mut progress = 0.5;
+          for _ in 0..16 {
+            let error = start * progress + change * easing.integral(progress) - target;
+            let rate = start + change * easing.apply(progress);
+            if rate.abs() < 1e-12 {
+              break;
+            }
+            progress = (progress - error / rate).clamp(0.0, 1.0);
+          }
+          progress
+        }
+      };
+      return time + from.at.as_secs_f64() + length * progress.clamp(0.0, 1.0);
+    }
+    // Past the last keyframe, the value holds.
+    let last = f(&self.keyframes[self.keyframes.len() - 1].value);
+    match last > 0.0 {
+      true => time + total + remaining / last,
+      false => f64::INFINITY,
+    }
+  }
+}
+
+impl<T: Interpolate> Animated<T> {
+  /// Get the value at `elapsed`.
+  pub fn value_at(&self, elapsed: Duration) -> T {
+    let (from, to, t) = self.segment(elapsed);
+    from.interpolate(to, t)
+  }
+}
+
+impl<T> From<T> for Animated<T> {
+  fn from(value: T) -> Animated<T> {
+    Animated::new(value)
+  }
+}
+
+/// A message in a [playlist](Rain::with_message_playlist).
+///
+/// Color, speed and effect default to the rain's own message settings unless
+/// overridden on the entry.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PlaylistEntry {
+  text: Text<'static>,
+  duration: Duration,
+  color: Option<Color>,
+  speed: Option<RainSpeed>,
+  effect: Option<MessageEffect>,
+}
+
+impl PlaylistEntry {
+  /// Construct an entry that shows `text` for `duration`.
+  pub fn new(text: &str, duration: Duration) -> PlaylistEntry {
+    PlaylistEntry::from_text(text.to_string(), duration)
+  }
+
+  /// Construct an entry that shows rich `text`, with its own span styles, for `duration`.
+  pub fn from_text(text: impl Into<Text<'static>>, duration: Duration) -> PlaylistEntry {
+    PlaylistEntry {
+      text: text.into(),
+      duration,
+      color: None,
+      speed: None,
+      effect: None,
+    }
+  }
+
+  /// Set the color of this entry.
+  pub fn with_color(mut self, color: Color) -> PlaylistEntry {
+    self.color = Some(color);
+    self
+  }
+
+  /// Set the drop-in speed of this entry.
+  pub fn with_speed(mut self, speed: RainSpeed) -> PlaylistEntry {
+    self.speed = Some(speed);
+    self
+  }
+
+  /// Set the effect of this entry.
+  pub fn with_effect(mut self, effect: MessageEffect) -> PlaylistEntry {
+    self.effect = Some(effect);
+    self
+  }
+}
+
+/// How tumbling particles, like leaves, petals and confetti, move while they fall.
+///
+/// Particles swing from side to side on a sinusoidal path, and turn by cycling through
+/// the [character set](Rain::with_character_set) in order.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Tumble {
+  /// How far particles swing to each side, in cells.
+  pub sway: f64,
+  /// How long a full swing takes, varying a bit per particle.
+  pub sway_period: Duration,
+  /// How long each glyph is shown before the particle turns to the next one.
+  pub spin_interval: Duration,
+}
+
+/// Settings for the classic matrix look, where drops are locked to columns and every
+/// column holds at most one stream.
+///
+/// The [density](Rain::with_rain_density) sets how often columns are active.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ColumnLock {
+  /// How many cells apart columns are, e.g. `2` for full-width kana.
+  pub spacing: u16,
+  /// The chance that a stream stops partway down the screen, after which it erases
+  /// from the top.
+  pub stop_chance: f64,
+  /// How long the head of a new stream flashes white.
+  pub head_flash: Duration,
+}
+
+impl Default for ColumnLock {
+  fn default() -> ColumnLock {
+    ColumnLock {
+      spacing: 1,
+      stop_chance: 0.3,
+      head_flash: Duration::from_millis(150),
+    }
+  }
+}
+
+/// How finely drops are placed within terminal cells.
+///
+/// At a finer resolution, drops are drawn as dots on a grid of several dots per cell
+/// instead of with the character set, so they move smoothly and look finer.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum Resolution {
+  /// A glyph from the character set per cell.
+  #[default]
+  Cell,
+
+  /// Half blocks, with 1x2 dots per cell.
+  HalfBlock,
+
+  /// Braille patterns, with 2x4 dots per cell.
+  Braille,
+}
+
+impl Resolution {
+  /// The number of dots across and down each cell.
+  fn grid(self) -> (u8, u8) {
+    match self {
+      Resolution::Cell => (1, 1),
+      Resolution::HalfBlock => (1, 2),
+      Resolution::Braille => (2, 4),
+    }
+  }
+
+  /// The character showing the dots of a cell, where bit `column * rows + row` of
+  /// `dots` is set for every dot that is on.
+  fn symbol(self, dots: u8) -> char {
+    match self {
+      Resolution::Cell => '█',
+      Resolution::HalfBlock => match dots & 0b11 {
+        0b01 => '▀',
+        0b10 => '▄',
+        _ => '█',
+      },
+      Resolution::Braille => {
+        // Braille numbers the dots of the first three rows down each column, and the
+        // bottom row after them.
+        let bits = (dots & 0b0111) | (dots & 0b0111_0000) >> 1 | (dots & 0b1000) << 3 | (dots & 0b1000_0000);
+        char::from_u32(0x2800 + bits as u32).unwrap_or(' ')
+      }
+    }
+  }
+
+  /// The dots a glyph covers in its cell, given the part of the cell it spans along
+  /// its track and where it is across the track, both from 0 to 1.
+  fn dots(self, axes: FallAxes, span: (f64, f64), across: f64) -> u8 {
+    let (columns, rows) = self.grid();
+    let (along_dots, across_dots) = if axes.horizontal { (columns, rows) } else { (rows, columns) };
+    let dot = |fraction: f64, dots: u8| ((fraction * dots as f64) as u8).min(dots - 1);
+    let (mut first, mut last) = (dot(span.0, along_dots), dot(span.1, along_dots));
+    if axes.reversed {
+      (first, last) = (along_dots - 1 - last, along_dots - 1 - first);
+    }
+    let across = dot(across, across_dots);
+    (first..=last).fold(0, |dots, along| {
+      let (column, row) = if axes.horizontal { (along, across) } else { (across, along) };
+      dots | 1 << (column * rows + row)
+    })
+  }
+}
+
+impl FromStr for Resolution {
+  type Err = ParseOptionError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "cell" => Ok(Resolution::Cell),
+      "half-block" | "halfblock" => Ok(Resolution::HalfBlock),
+      "braille" => Ok(Resolution::Braille),
+      _ => Err(ParseOptionError::new("resolution", s)),
+    }
+  }
+}
+
+/// A shape, like a logo, that controls where glyphs appear or how they're colored.
+///
+/// The mask is scaled to fit the render area, keeping its aspect ratio, and centered.
+/// Every pixel is either unset or set to a color.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Mask {
+  width: usize,
+  height: usize,
+  /// How many terminal rows one row of pixels covers at the same scale as one column.
+  row_aspect: f64,
+  pixels: Arc<[Option<Color>]>,
+}
+
+impl Mask {
+  /// Construct a mask from image pixels in row-major order.
+  ///
+  /// Pixels are assumed to be square, so two rows of pixels cover about as much as one
+  /// terminal row. Missing pixels are unset.
+  pub fn from_pixels(width: usize, height: usize, mut pixels: Vec<Option<Color>>) -> Mask {
+    pixels.resize(width * height, None);
+    Mask {
+      width,
+      height,
+      row_aspect: 0.5,
+      pixels: pixels.into(),
+    }
+  }
+
+  /// Construct a mask from ASCII art, with one pixel per character.
+  ///
+  /// Every character other than a space is set to `color`.
+  ///
+  /// ```
+  /// use ratatui::style::Color;
+  /// use tui_rain_cli::Mask;
+  ///
+  /// let heart = Mask::from_ascii(" ## ## \n#######\n ##### \n   #   ", Color::Red);
+  /// ```
+  pub fn from_ascii(art: &str, color: Color) -> Mask {
+    let lines: Vec<&str> = art.lines().collect();
+    let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    let pixels = lines
+      .iter()
+      .flat_map(|line| {
+        let set = line.chars().map(|c| (!c.is_whitespace()).then_some(color));
+        set.chain(std::iter::repeat(None)).take(width)
+      })
+      .collect();
+    Mask {
+      width,
+      height: lines.len(),
+      row_aspect: 1.0,
+      pixels,
+    }
+  }
+
+  /// Get the pixel covering the cell at (x, y) of an area, or `None` if it's unset or
+  /// outside the mask.
+  fn get(&self, x: u16, y: u16, width: u16, height: u16) -> Option<Color> {
+    if self.width == 0 || self.height == 0 {
+      return None;
+    }
+    let (mask_width, mask_height) = (self.width as f64, self.height as f64 * self.row_aspect);
+    let scale = (width as f64 / mask_width).min(height as f64 / mask_height);
+    let left = (width as f64 - mask_width * scale) / 2.0;
+    let top = (height as f64 - mask_height * scale) / 2.0;
+    let column = ((x as f64 + 0.5 - left) / scale).floor();
+    let row = ((y as f64 + 0.5 - top) / scale / self.row_aspect).floor();
+    if column < 0.0 || row < 0.0 || column >= mask_width || row >= self.height as f64 {
+      return None;
+    }
+    self.pixels[row as usize * self.width + column as usize]
+  }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Rain {
+  elapsed: Duration,
+  seed: u64,
+  rain_density: Animated<RainDensity>,
+  rain_speed: Animated<RainSpeed>,
+  rain_speed_variance: f64,
+  speed_distribution: Distribution,
+  tail_variation: Variation,
+  brightness_variation: Variation,
+  head_color_variation: Variation,
+  depth_correlation: bool,
+  tail_lifespan: Animated<Duration>,
+  color: Animated<Color>,
+  head_color: Color,
+  bold_dim_effect: bool,
+  noise_interval: Animated<Duration>,
+  character_set: CharacterSet,
+  color_palette: Vec<Color>,
+  tumble: Option<Tumble>,
+  direction: FallDirection,
+  gravity: Option<f64>,
+  column_lock: Option<ColumnLock>,
+  resolution: Resolution,
+  capabilities: Capabilities,
+  message: Text<'static>,
+  message_color: Color,
+  message_speed: RainSpeed,
+  message_start: Duration,
+  message_effect: MessageEffect,
+  message_playlist: Vec<PlaylistEntry>,
+  message_position: MessagePosition,
+  message_alignment: MessageAlignment,
+  message_padding: Padding,
+  message_edge: MessageEdge,
+  message_backdrop: MessageBackdrop,
+  message_backdrop_margin: Padding,
+  backdrop_drops: BackdropDrops,
+  decode_timeout: Duration,
+  mask: Option<Mask>,
+  mask_mode: MaskMode,
+  verbose: bool,
+}
+
+impl Rain {
+  /// Construct a new rain widget with defaults for matrix rain.
+  pub fn new_matrix(elapsed: Duration) -> Rain {
+    Rain {
+      rain_density: RainDensity::Normal.into(),
+      rain_speed: RainSpeed::Slow.into(),
+      rain_speed_variance: 0.5,
+      tail_lifespan: Duration::from_secs(2).into(),
+      color: Color::LightGreen.into(),
+      noise_interval: Duration::from_secs(5).into(),
+      character_set: CharacterSet::HalfKana,
+      message_color: Color::Green,
+      ..Rain::base(elapsed)
+    }
+  }
+
+  /// Construct a new rain widget with defaults for standard rain.
+  pub fn new_rain(elapsed: Duration) -> Rain {
+    Rain {
+      rain_density: RainDensity::Dense.into(),
+      rain_speed: RainSpeed::Fast.into(),
+      rain_speed_variance: 0.5,
+      tail_lifespan: Duration::from_millis(250).into(),
+      color: Color::LightBlue.into(),
+      noise_interval: Duration::from_secs(1).into(),
+      character_set: CharacterSet::UnicodeRange { start: 0x7c, len: 1 },
+      message_color: Color::Blue,
+      ..Rain::base(elapsed)
+    }
+  }
+
+  /// Construct a new rain widget with defaults for snow.
+  pub fn new_snow(elapsed: Duration) -> Rain {
+    Rain {
+      rain_density: RainDensity::Dense.into(),
+      rain_speed: RainSpeed::Absolute { speed: 2.0 }.into(),
+      rain_speed_variance: 0.1,
+      tail_lifespan: Duration::from_millis(500).into(),
+      color: Color::White.into(),
+      noise_interval: Duration::from_secs(1).into(),
+      character_set: CharacterSet::UnicodeRange { start: 0x2a, len: 1 },
+      message_color: Color::Gray,
+      ..Rain::base(elapsed)
+    }
+  }
+
+  /// Construct a new rain widget with defaults for emoji soup.
+  ///
+  /// Terminals that render emojis as two characters wide will not enjoy this.
+  pub fn new_emoji_soup(elapsed: Duration) -> Rain {
+    Rain {
+      rain_density: RainDensity::Dense.into(),
+      rain_speed: RainSpeed::Normal.into(),
+      rain_speed_variance: 0.1,
+      tail_lifespan: Duration::from_millis(500).into(),
+      color: Color::White.into(),
+      noise_interval: Duration::from_secs(1).into(),
+      character_set: CharacterSet::UnicodeRange { start: 0x1f600, len: 80 },
+      message_color: Color::Yellow,
+      ..Rain::base(elapsed)
+    }
+  }
+
+  /// The settings shared by all presets, which each set the look of their drops and
+  /// message on top.
+  fn base(elapsed: Duration) -> Rain {
+    Rain {
+      elapsed,
+      seed: 1234,
+      rain_density: RainDensity::Normal.into(),
+      rain_speed: RainSpeed::Normal.into(),
+      rain_speed_variance: 0.5,
+      speed_distribution: Distribution::Uniform,
+      tail_variation: Variation::default(),
+      brightness_variation: Variation::default(),
+      head_color_variation: Variation::default(),
+      depth_correlation: false,
+      tail_lifespan: Duration::from_secs(1).into(),
+      color: Color::White.into(),
+      head_color: Color::White,
+      bold_dim_effect: true,
+      noise_interval: Duration::from_secs(1).into(),
+      character_set: CharacterSet::HalfKana,
+      color_palette: vec![],
+      tumble: None,
+      direction: FallDirection::Down,
+      gravity: None,
+      column_lock: None,
+      resolution: Resolution::Cell,
+      capabilities: Capabilities::default(),
+      message: Text::default(),
+      message_color: Color::White,
+      message_speed: RainSpeed::Slow,
+      message_start: Duration::ZERO,
+      message_effect: MessageEffect::DropIn,
+      message_playlist: vec![],
+      message_position: MessagePosition::Center,
+      message_alignment: MessageAlignment::Center,
+      message_padding: Padding::horizontal(1),
+      message_edge: MessageEdge::Top,
+      message_backdrop: MessageBackdrop::None,
+      message_backdrop_margin: Padding::horizontal(1),
+      backdrop_drops: BackdropDrops::PassBehind,
+      decode_timeout: Duration::from_secs(20),
+      mask: None,
+      mask_mode: MaskMode::Tint,
+      verbose: false,
+    }
+  }
+
+  /// Set the random seed for the generation.
+  ///
+  /// The random seed can be configured. Given a constant screen size, results should
+  /// be reproducible across executions, operating systems, and architectures.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::Rain;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_matrix(elapsed)
+  ///     .with_seed(1234);
+  /// ```
+  pub fn with_seed(mut self, seed: u64) -> Rain {
+    self.seed = seed;
+    self
+  }
+
+  /// Apply the settings shared by all [effects](Effect).
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{EffectConfig, Rain};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_snow(elapsed)
+  ///     .with_config(&EffectConfig::default());
+  /// ```
+  pub fn with_config(self, config: &EffectConfig) -> Rain {
+    self
+      .with_seed(config.seed)
+      .with_rain_density(config.density)
+      .with_rain_speed(config.speed)
+      .with_rain_speed_variance(config.speed_variance)
+      .with_tail_lifespan(config.tail_lifespan)
+      .with_color(config.color)
+      .with_head_color(config.head_color)
+      .with_bold_dim_effect(config.bold_dim_effect)
+      .with_message_color(config.message_color)
+      .with_resolution(config.resolution)
+  }
+
+  /// Set the message to be displayed.
+  ///
+  /// The message will be displayed in the center of the screen.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::Rain;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_matrix(elapsed)
+  ///    .with_message("Hello, World!");
+  /// ```
+  pub fn with_message(mut self, message: &str) -> Rain {
+    self.message = Text::raw(message.to_string());
+    self
+  }
+
+  /// Set a rich text message to be displayed.
+  ///
+  /// Styles of the text, its lines and spans are applied on top of the
+  /// [message color](Rain::with_message_color), and stay attached to the right
+  /// characters when the message is wrapped.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use ratatui::{style::Stylize, text::Line};
+  /// use tui_rain_cli::Rain;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_snow(elapsed)
+  ///    .with_message_text(Line::from(vec!["Happy ".into(), "2027".bold(), " team".red()]));
+  /// ```
+  pub fn with_message_text(mut self, message: impl Into<Text<'static>>) -> Rain {
+    self.message = message.into();
+    self
+  }
+
+  /// Set the message color.
+  ///
+  /// The message color can be configured.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::Rain;
+  /// use ratatui::style::Color;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_matrix(elapsed)
+  ///    .with_message_color(Color::Green);
+  /// ```
+  pub fn with_message_color(mut self, message_color: Color) -> Rain {
+    self.message_color = message_color;
+    self
+  }
+
+  /// Set the target speed for the message.
+  ///
+  /// Speed can be configured as an absolute value of pixels per second, or as a
+  /// preset.
+  ///
+  /// For an absolute speed in pixels per second:
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{Rain, RainSpeed};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_matrix(elapsed)
+  ///     .with_message_speed(RainSpeed::Absolute {
+  ///         speed: 10.0,
+  ///     });
+  /// ```
+  ///
+  /// Preset options include:
+  ///
+  /// - `RainSpeed::Slow`
+  /// - `RainSpeed::Normal`
+  /// - `RainSpeed::Fast`
+  pub fn with_message_speed(mut self, message_speed: RainSpeed) -> Rain {
+    self.message_speed = message_speed;
+    self
+  }
+
+  /// Set when the current message first appeared.
+  ///
+  /// The message drops in from the top of the screen, starting at this point on the
+  /// same clock as `elapsed`. When the message text changes while rendering, moving the
+  /// start to the time of the change replays the drop-in animation for the new text:
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::Rain;
+  ///
+  /// let elapsed = Duration::from_secs(65);
+  ///
+  /// Rain::new_matrix(elapsed)
+  ///     .with_message("Build passed")
+  ///     .with_message_start(Duration::from_secs(60));
+  /// ```
+  pub fn with_message_start(mut self, message_start: Duration) -> Rain {
+    self.message_start = message_start;
+    self
+  }
+
+  /// Set how the message is animated onto the screen.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{MessageEffect, Rain};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_matrix(elapsed)
+  ///     .with_message("Wake up, Neo...")
+  ///     .with_message_effect(MessageEffect::Blink);
+  /// ```
+  ///
+  /// Options include:
+  ///
+  /// - `MessageEffect::DropIn` (default)
+  /// - `MessageEffect::Static`
+  /// - `MessageEffect::Blink`
+  /// - `MessageEffect::Decode`
+  pub fn with_message_effect(mut self, message_effect: MessageEffect) -> Rain {
+    self.message_effect = message_effect;
+    self
+  }
+
+  /// Set a playlist of messages to cycle through.
+  ///
+  /// Each entry is shown for its own duration, after which the next one replays the
+  /// drop-in animation. Once the last entry has been shown, the playlist starts over.
+  /// The schedule only depends on `elapsed` and the [message start](Rain::with_message_start).
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use ratatui::style::Color;
+  /// use tui_rain_cli::{MessageEffect, PlaylistEntry, Rain};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_snow(elapsed)
+  ///     .with_message_playlist(vec![
+  ///         PlaylistEntry::new("Happy New Year!", Duration::from_secs(10))
+  ///             .with_color(Color::Yellow),
+  ///         PlaylistEntry::new("See you in 2027", Duration::from_secs(5))
+  ///             .with_effect(MessageEffect::Blink),
+  ///     ]);
+  /// ```
+  ///
+  /// While the playlist is empty, the [single message](Rain::with_message) is shown.
+  pub fn with_message_playlist(mut self, message_playlist: Vec<PlaylistEntry>) -> Rain {
+    self.message_playlist = message_playlist;
+    self
+  }
+
+  /// Set where the message is placed on the screen.
+  ///
+  /// The message can be placed at a named position:
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{MessagePosition, Rain};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_matrix(elapsed)
+  ///     .with_message("Build passed")
+  ///     .with_message_position(MessagePosition::BottomRight);
+  /// ```
+  ///
+  /// Or at a percentage of the free space on each axis:
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{MessagePosition, Rain};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_matrix(elapsed)
+  ///     .with_message("Build passed")
+  ///     .with_message_position(MessagePosition::Percent { x: 80, y: 10 });
+  /// ```
+  pub fn with_message_position(mut self, message_position: MessagePosition) -> Rain {
+    self.message_position = message_position;
+    self
+  }
+
+  /// Set how the lines of a multi-line message are aligned with each other.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{MessageAlignment, Rain};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_matrix(elapsed)
+  ///     .with_message("Happy New Year!\nfrom all of us")
+  ///     .with_message_alignment(MessageAlignment::Left);
+  /// ```
+  pub fn with_message_alignment(mut self, message_alignment: MessageAlignment) -> Rain {
+    self.message_alignment = message_alignment;
+    self
+  }
+
+  /// Set the space kept free between the message and the screen edges.
+  ///
+  /// The message is wrapped to fit the remaining width. By default, one column is
+  /// kept free on each side:
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use ratatui::widgets::Padding;
+  /// use tui_rain_cli::Rain;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_matrix(elapsed)
+  ///     .with_message_padding(Padding::new(4, 4, 2, 2));
+  /// ```
+  pub fn with_message_padding(mut self, message_padding: Padding) -> Rain {
+    self.message_padding = message_padding;
+    self
+  }
+
+  /// Set the screen edge the message scrolls in from.
+  ///
+  /// Only applies to the [drop-in](MessageEffect::DropIn) effect. The message travels
+  /// from just beyond the edge to its position at the [message speed](Rain::with_message_speed).
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{MessageEdge, Rain};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_matrix(elapsed)
+  ///     .with_message("Incoming")
+  ///     .with_message_edge(MessageEdge::Left);
+  /// ```
+  pub fn with_message_edge(mut self, message_edge: MessageEdge) -> Rain {
+    self.message_edge = message_edge;
+    self
+  }
+
+  /// Set a backdrop behind the message to keep it readable.
+  ///
+  /// By default, rain shows through the spaces in and around the message. A backdrop
+  /// clears, dims or fills the area around it, or frames it with a border:
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use ratatui::widgets::BorderType;
+  /// use tui_rain_cli::{MessageBackdrop, Rain};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_matrix(elapsed)
+  ///     .with_message("Wake up, Neo...")
+  ///     .with_message_backdrop(MessageBackdrop::Border {
+  ///         border_type: BorderType::Rounded,
+  ///     });
+  /// ```
+  pub fn with_message_backdrop(mut self, message_backdrop: MessageBackdrop) -> Rain {
+    self.message_backdrop = message_backdrop;
+    self
+  }
 
-  /// Half-width Japanese Kana characters. This is the closest to the original.
+  /// Set the space the backdrop extends beyond the message.
   ///
-  /// Equivalent to `CharacterSet::UnicodeRange { start: 0xFF66, len: 56 }`.
-  HalfKana,
-
-  /// The lowercase English alphabet.
+  /// ```
+  /// use std::time::Duration;
+  /// use ratatui::widgets::Padding;
+  /// use tui_rain_cli::{MessageBackdrop, Rain};
   ///
-  /// Equivalent to `CharacterSet::UnicodeRange { start: 0x61, len: 26 }`.
-  Lowercase,
-}
-
-impl CharacterSet {
-  fn get(&self, seed: u32) -> char {
-    match self {
-      CharacterSet::Explicit { options } => options[seed as usize % options.len()],
-      CharacterSet::UnicodeRange { start, len } => char::from_u32((seed % len) + start).unwrap(),
-      CharacterSet::HalfKana => CharacterSet::UnicodeRange { start: 0xFF66, len: 56 }.get(seed),
-      CharacterSet::Lowercase => CharacterSet::UnicodeRange { start: 0x61, len: 26 }.get(seed),
-    }
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_matrix(elapsed)
+  ///     .with_message_backdrop(MessageBackdrop::Clear)
+  ///     .with_message_backdrop_margin(Padding::symmetric(2, 1));
+  /// ```
+  pub fn with_message_backdrop_margin(mut self, message_backdrop_margin: Padding) -> Rain {
+    self.message_backdrop_margin = message_backdrop_margin;
+    self
   }
 
-  fn size(&self) -> usize {
-    match self {
-      CharacterSet::Explicit { options } => options.len(),
-      CharacterSet::UnicodeRange { start: _, len } => *len as usize,
-      CharacterSet::HalfKana => 56,
-      CharacterSet::Lowercase => 26,
-    }
+  /// Set whether drops pass behind the message backdrop or stop at it.
+  ///
+  /// Stopping drops leaves the area below the backdrop dry, as if it were sheltering
+  /// it from the rain:
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{BackdropDrops, MessageBackdrop, Rain};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_rain(elapsed)
+  ///     .with_message("Under cover")
+  ///     .with_message_backdrop(MessageBackdrop::Clear)
+  ///     .with_backdrop_drops(BackdropDrops::Stop);
+  /// ```
+  pub fn with_backdrop_drops(mut self, backdrop_drops: BackdropDrops) -> Rain {
+    self.backdrop_drops = backdrop_drops;
+    self
   }
-}
-
-#[derive(Clone, PartialEq, Debug)]
-pub struct Rain {
-  elapsed: Duration,
-  seed: u64,
-  rain_density: RainDensity,
-  rain_speed: RainSpeed,
-  rain_speed_variance: f64,
-  tail_lifespan: Duration,
-  color: Color,
-  head_color: Color,
-  bold_dim_effect: bool,
-  noise_interval: Duration,
-  character_set: CharacterSet,
-  message: String,
-  message_color: Color,
-  message_speed: RainSpeed,
-  verbose: bool,
-}
 
-impl Rain {
-  /// Construct a new rain widget with defaults for matrix rain.
-  pub fn new_matrix(elapsed: Duration) -> Rain {
-    Rain {
-      elapsed,
-      seed: 1234,
-      rain_density: RainDensity::Normal,
-      rain_speed: RainSpeed::Slow,
-      rain_speed_variance: 0.5,
-      tail_lifespan: Duration::from_secs(2),
-      color: Color::LightGreen,
-      head_color: Color::White,
-      bold_dim_effect: true,
-      noise_interval: Duration::from_secs(5),
-      character_set: CharacterSet::HalfKana,
-      message: String::from(""),
-      message_color: Color::Green,
-      message_speed: RainSpeed::Slow,
-      verbose: false,
-    }
+  /// Set the longest time the [decode effect](MessageEffect::Decode) takes.
+  ///
+  /// Characters are decoded as drop heads pass over them, so sparse or slow rain can
+  /// take a long time to reach every character. Whatever is still hidden after this
+  /// time is decoded at once:
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{MessageEffect, Rain};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_matrix(elapsed)
+  ///     .with_message("Wake up, Neo...")
+  ///     .with_message_effect(MessageEffect::Decode)
+  ///     .with_decode_timeout(Duration::from_secs(10));
+  /// ```
+  pub fn with_decode_timeout(mut self, decode_timeout: Duration) -> Rain {
+    self.decode_timeout = decode_timeout;
+    self
   }
 
-  /// Construct a new rain widget with defaults for standard rain.
-  pub fn new_rain(elapsed: Duration) -> Rain {
-    Rain {
-      elapsed,
-      seed: 1234,
-      rain_density: RainDensity::Dense,
-      rain_speed: RainSpeed::Fast,
-      rain_speed_variance: 0.5,
-      tail_lifespan: Duration::from_millis(250),
-      color: Color::LightBlue,
-      head_color: Color::White,
-      bold_dim_effect: true,
-      noise_interval: Duration::from_secs(1),
-      character_set: CharacterSet::UnicodeRange { start: 0x7c, len: 1 },
-      message: String::from(""),
-      message_color: Color::Blue,
-      message_speed: RainSpeed::Slow,
-      verbose: false,
-    }
+  /// Set a mask that shapes or colors the rain, like a logo.
+  ///
+  /// By default, glyphs passing over the mask take its color so it shows through the
+  /// rain. See [`with_mask_mode`](Rain::with_mask_mode) for other options.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use ratatui::style::Color;
+  /// use tui_rain_cli::{Mask, Rain};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_matrix(elapsed)
+  ///     .with_mask(Mask::from_ascii("#   #\n ## \n#   #", Color::Red));
+  /// ```
+  pub fn with_mask(mut self, mask: Mask) -> Rain {
+    self.mask = Some(mask);
+    self
   }
 
-  /// Construct a new rain widget with defaults for snow.
-  pub fn new_snow(elapsed: Duration) -> Rain {
-    Rain {
-      elapsed,
-      seed: 1234,
-      rain_density: RainDensity::Dense,
-      rain_speed: RainSpeed::Absolute { speed: 2.0 },
-      rain_speed_variance: 0.1,
-      tail_lifespan: Duration::from_millis(500),
-      color: Color::White,
-      head_color: Color::White,
-      bold_dim_effect: true,
-      noise_interval: Duration::from_secs(1),
-      character_set: CharacterSet::UnicodeRange { start: 0x2a, len: 1 },
-      message: String::from(""),
-      message_color: Color::Gray,
-      message_speed: RainSpeed::Slow,
-      verbose: false,
-    }
+  /// Set how the [mask](Rain::with_mask) affects the rain.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use ratatui::style::Color;
+  /// use tui_rain_cli::{Mask, MaskMode, Rain};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_rain(elapsed)
+  ///     .with_mask(Mask::from_ascii("####\n####", Color::White))
+  ///     .with_mask_mode(MaskMode::Cutout);
+  /// ```
+  pub fn with_mask_mode(mut self, mask_mode: MaskMode) -> Rain {
+    self.mask_mode = mask_mode;
+    self
   }
 
-  /// Construct a new rain widget with defaults for emoji soup.
+  /// Make drops tumble like leaves, petals or confetti.
   ///
-  /// Terminals that render emojis as two characters wide will not enjoy this.
-  pub fn new_emoji_soup(elapsed: Duration) -> Rain {
-    Rain {
-      elapsed,
-      seed: 1234,
-      rain_density: RainDensity::Dense,
-      rain_speed: RainSpeed::Normal,
-      rain_speed_variance: 0.1,
-      tail_lifespan: Duration::from_millis(500),
-      color: Color::White,
-      head_color: Color::White,
-      bold_dim_effect: true,
-      noise_interval: Duration::from_secs(1),
-      character_set: CharacterSet::UnicodeRange { start: 0x1f600, len: 80 },
-      message: String::from(""),
-      message_color: Color::Yellow,
-      message_speed: RainSpeed::Slow,
-      verbose: false,
-    }
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{CharacterSet, Rain, Tumble};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_snow(elapsed)
+  ///     .with_character_set(CharacterSet::Explicit { options: vec!['/', '-', '\\', '|'] })
+  ///     .with_tumble(Tumble {
+  ///         sway: 2.0,
+  ///         sway_period: Duration::from_secs(3),
+  ///         spin_interval: Duration::from_millis(400),
+  ///     });
+  /// ```
+  pub fn with_tumble(mut self, tumble: Tumble) -> Rain {
+    self.tumble = Some(tumble);
+    self
   }
 
-  /// Set the random seed for the generation.
+  /// Set colors for drops to pick from, instead of the single [color](Rain::with_color).
   ///
-  /// The random seed can be configured. Given a constant screen size, results should
-  /// be reproducible across executions, operating systems, and architectures.
+  /// Every drop takes one of these colors at random for its whole length, head
+  /// included. An empty palette uses the color and head color again.
   ///
   /// ```
   /// use std::time::Duration;
-  /// use tui_rain::Rain;
+  /// use ratatui::style::Color;
+  /// use tui_rain_cli::Rain;
   ///
   /// let elapsed = Duration::from_secs(5);
   ///
-  /// Rain::new_matrix(elapsed)
-  ///     .with_seed(1234);
+  /// Rain::new_snow(elapsed)
+  ///     .with_color_palette(vec![Color::Red, Color::Yellow, Color::Blue]);
   /// ```
-  pub fn with_seed(mut self, seed: u64) -> Rain {
-    self.seed = seed;
+  pub fn with_color_palette(mut self, color_palette: Vec<Color>) -> Rain {
+    self.color_palette = color_palette;
     self
   }
 
-  /// Set the message to be displayed.
+  /// Set the direction drops fall in.
   ///
-  /// The message will be displayed in the center of the screen.
+  /// Tracks run along the axis closest to the direction, so streams to the left or
+  /// right span the width of the screen.
   ///
   /// ```
   /// use std::time::Duration;
-  /// use tui_rain::Rain;
+  /// use tui_rain_cli::{FallDirection, Rain};
   ///
   /// let elapsed = Duration::from_secs(5);
   ///
-  /// Rain::new_matrix(elapsed)
-  ///    .with_message("Hello, World!");
+  /// Rain::new_rain(elapsed)
+  ///     .with_direction(FallDirection::Angle { degrees: 20.0 });
   /// ```
-  pub fn with_message(mut self, message: &str) -> Rain {
-    self.message = message.to_string();
+  pub fn with_direction(mut self, direction: FallDirection) -> Rain {
+    self.direction = direction;
     self
   }
 
-  /// Set the message color.
+  /// Make drops start at rest and accelerate under gravity, in pixels / second², until
+  /// they reach their [speed](Rain::with_rain_speed), which becomes their terminal
+  /// velocity. Tails grow with the speed.
   ///
-  /// The message color can be configured.
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::Rain;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_rain(elapsed)
+  ///     .with_gravity(20.0);
+  /// ```
+  pub fn with_gravity(mut self, gravity: f64) -> Rain {
+    self.gravity = Some(gravity);
+    self
+  }
+
+  /// Lock drops to columns, for the classic matrix look.
   ///
   /// ```
   /// use std::time::Duration;
-  /// use tui_rain::Rain;
-  /// use ratatui::style::Color;
+  /// use tui_rain_cli::{ColumnLock, Rain};
   ///
   /// let elapsed = Duration::from_secs(5);
   ///
   /// Rain::new_matrix(elapsed)
-  ///    .with_message_color(Color::Green);
+  ///     .with_column_lock(ColumnLock { spacing: 2, ..ColumnLock::default() });
   /// ```
-  pub fn with_message_color(mut self, message_color: Color) -> Rain {
-    self.message_color = message_color;
+  pub fn with_column_lock(mut self, column_lock: ColumnLock) -> Rain {
+    self.column_lock = Some(column_lock);
     self
   }
 
-  /// Set the target speed for the message.
+  /// Set how finely drops are placed within cells. At a finer resolution, drops are
+  /// drawn as dots instead of with the character set.
   ///
-  /// Speed can be configured as an absolute value of pixels per second, or as a
-  /// preset.
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{Rain, Resolution};
   ///
-  /// For an absolute speed in pixels per second:
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_snow(elapsed)
+  ///     .with_resolution(Resolution::Braille);
+  /// ```
+  pub fn with_resolution(mut self, resolution: Resolution) -> Rain {
+    self.resolution = resolution;
+    self
+  }
+
+  /// Set what the terminal can show. Without unicode, kana, emoji and other glyphs are
+  /// swapped for ASCII ones and drops are drawn in whole cells.
+  ///
+  /// Colors and dim are degraded for the whole frame with [`Capabilities::degrade`].
   ///
   /// ```
   /// use std::time::Duration;
-  /// use tui_rain::{Rain, RainSpeed};
+  /// use tui_rain_cli::{Capabilities, Rain};
   ///
   /// let elapsed = Duration::from_secs(5);
   ///
   /// Rain::new_matrix(elapsed)
-  ///     .with_message_speed(RainSpeed::Absolute {
-  ///         speed: 10.0,
-  ///     });
+  ///     .with_capabilities("16,ascii".parse::<Capabilities>().unwrap());
   /// ```
-  ///
-  /// Preset options include:
-  ///
-  /// - `RainSpeed::Slow`
-  /// - `RainSpeed::Normal`
-  /// - `RainSpeed::Fast`
-  pub fn with_message_speed(mut self, message_speed: RainSpeed) -> Rain {
-    self.message_speed = message_speed;
+  pub fn with_capabilities(mut self, capabilities: Capabilities) -> Rain {
+    self.capabilities = capabilities;
     self
   }
 
@@ -311,7 +1842,7 @@ impl Rain {
   ///
   /// ```
   /// use std::time::Duration;
-  /// use tui_rain::{Rain, RainDensity};
+  /// use tui_rain_cli::{Rain, RainDensity};
   ///
   /// Rain::new_matrix(Duration::from_secs(0))
   ///     .with_rain_density(RainDensity::Absolute {
@@ -322,7 +1853,7 @@ impl Rain {
   ///
   /// ```
   /// use std::time::Duration;
-  /// use tui_rain::{Rain, RainDensity};
+  /// use tui_rain_cli::{Rain, RainDensity};
   ///
   /// Rain::new_matrix(Duration::from_secs(0))
   ///     .with_rain_density(RainDensity::Relative {
@@ -338,8 +1869,8 @@ impl Rain {
   /// - `RainDensity::Sparse`
   /// - `RainDensity::Normal`
   /// - `RainDensity::Dense`
-  pub fn with_rain_density(mut self, rain_density: RainDensity) -> Rain {
-    self.rain_density = rain_density;
+  pub fn with_rain_density(mut self, rain_density: impl Into<Animated<RainDensity>>) -> Rain {
+    self.rain_density = rain_density.into();
     self
   }
 
@@ -352,7 +1883,7 @@ impl Rain {
   ///
   /// ```
   /// use std::time::Duration;
-  /// use tui_rain::{Rain, RainSpeed};
+  /// use tui_rain_cli::{Rain, RainSpeed};
   ///
   /// let elapsed = Duration::from_secs(5);
   ///
@@ -367,8 +1898,8 @@ impl Rain {
   /// - `RainSpeed::Slow`
   /// - `RainSpeed::Normal`
   /// - `RainSpeed::Fast`
-  pub fn with_rain_speed(mut self, rain_speed: RainSpeed) -> Rain {
-    self.rain_speed = rain_speed;
+  pub fn with_rain_speed(mut self, rain_speed: impl Into<Animated<RainSpeed>>) -> Rain {
+    self.rain_speed = rain_speed.into();
     self
   }
 
@@ -382,7 +1913,7 @@ impl Rain {
   ///
   /// ```
   /// use std::time::Duration;
-  /// use tui_rain::Rain;
+  /// use tui_rain_cli::Rain;
   ///
   /// let elapsed = Duration::from_secs(5);
   ///
@@ -397,6 +1928,92 @@ impl Rain {
     self
   }
 
+  /// Set the distribution the speed of each drop is drawn from, spread by the
+  /// [speed variance](Rain::with_rain_speed_variance).
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{Distribution, Rain};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_rain(elapsed)
+  ///     .with_speed_distribution(Distribution::LogNormal);
+  /// ```
+  pub fn with_speed_distribution(mut self, speed_distribution: Distribution) -> Rain {
+    self.speed_distribution = speed_distribution;
+    self
+  }
+
+  /// Set how the tail lifespan varies from drop to drop.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{Distribution, Rain, Variation};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_rain(elapsed)
+  ///     .with_tail_variation(Variation { distribution: Distribution::Normal, variance: 0.5 });
+  /// ```
+  pub fn with_tail_variation(mut self, tail_variation: Variation) -> Rain {
+    self.tail_variation = tail_variation;
+    self
+  }
+
+  /// Set how the brightness varies from drop to drop. Drops below the target fade
+  /// towards black, and drops above it towards white.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{Distribution, Rain, Variation};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_rain(elapsed)
+  ///     .with_brightness_variation(Variation { distribution: Distribution::Uniform, variance: 0.6 });
+  /// ```
+  pub fn with_brightness_variation(mut self, brightness_variation: Variation) -> Rain {
+    self.brightness_variation = brightness_variation;
+    self
+  }
+
+  /// Set how the head color varies from drop to drop, between the
+  /// [head color](Rain::with_head_color) and the [color](Rain::with_color).
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{Distribution, Rain, Variation};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_matrix(elapsed)
+  ///     .with_head_color_variation(Variation { distribution: Distribution::Bimodal, variance: 0.5 });
+  /// ```
+  pub fn with_head_color_variation(mut self, head_color_variation: Variation) -> Rain {
+    self.head_color_variation = head_color_variation;
+    self
+  }
+
+  /// Set whether the tail lifespan and brightness of drops follow their speed, so slow
+  /// drops are also shorter and dimmer, as if they were further away.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{Distribution, Rain, Variation};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_rain(elapsed)
+  ///     .with_tail_variation(Variation { distribution: Distribution::Uniform, variance: 0.5 })
+  ///     .with_brightness_variation(Variation { distribution: Distribution::Uniform, variance: 0.5 })
+  ///     .with_depth_correlation(true);
+  /// ```
+  pub fn with_depth_correlation(mut self, depth_correlation: bool) -> Rain {
+    self.depth_correlation = depth_correlation;
+    self
+  }
+
   /// Set the tail lifespan for the rain.
   ///
   /// You can make the rain drop tails appear shorter / longer by configuring how long
@@ -404,7 +2021,7 @@ impl Rain {
   ///
   /// ```
   /// use std::time::Duration;
-  /// use tui_rain::Rain;
+  /// use tui_rain_cli::Rain;
   ///
   /// let elapsed = Duration::from_secs(5);
   ///
@@ -414,8 +2031,8 @@ impl Rain {
   ///
   /// The drop length is capped at the screen height to avoid strange wraparound
   /// effects.
-  pub fn with_tail_lifespan(mut self, tail_lifespan: Duration) -> Rain {
-    self.tail_lifespan = tail_lifespan;
+  pub fn with_tail_lifespan(mut self, tail_lifespan: impl Into<Animated<Duration>>) -> Rain {
+    self.tail_lifespan = tail_lifespan.into();
     self
   }
 
@@ -425,7 +2042,7 @@ impl Rain {
   ///
   /// ```
   /// use std::time::Duration;
-  /// use tui_rain::Rain;
+  /// use tui_rain_cli::Rain;
   ///
   /// let elapsed = Duration::from_secs(5);
   ///
@@ -436,8 +2053,8 @@ impl Rain {
   /// The color of the head is [independently configured](Rain::with_head_color). The
   /// bold / dim effects that automatically get applied over a drop's length may tweak
   /// the color inadvertently, but [this can be disabled](Rain::with_bold_dim_effect).
-  pub fn with_color(mut self, color: Color) -> Rain {
-    self.color = color;
+  pub fn with_color(mut self, color: impl Into<Animated<Color>>) -> Rain {
+    self.color = color.into();
     self
   }
 
@@ -447,7 +2064,7 @@ impl Rain {
   ///
   /// ```
   /// use std::time::Duration;
-  /// use tui_rain::Rain;
+  /// use tui_rain_cli::Rain;
   ///
   /// let elapsed = Duration::from_secs(5);
   ///
@@ -474,7 +2091,7 @@ impl Rain {
   ///
   /// ```
   /// use std::time::Duration;
-  /// use tui_rain::Rain;
+  /// use tui_rain_cli::Rain;
   ///
   /// let elapsed = Duration::from_secs(5);
   ///
@@ -492,7 +2109,7 @@ impl Rain {
   ///
   /// ```
   /// use std::time::Duration;
-  /// use tui_rain::Rain;
+  /// use tui_rain_cli::Rain;
   ///
   /// let elapsed = Duration::from_secs(5);
   ///
@@ -512,15 +2129,15 @@ impl Rain {
   ///
   /// ```
   /// use std::time::Duration;
-  /// use tui_rain::Rain;
+  /// use tui_rain_cli::Rain;
   ///
   /// let elapsed = Duration::from_secs(5);
   ///
   /// Rain::new_matrix(elapsed)
   ///     .with_noise_interval(Duration::from_secs(10));
   /// ```
-  pub fn with_noise_interval(mut self, noise_interval: Duration) -> Rain {
-    self.noise_interval = noise_interval;
+  pub fn with_noise_interval(mut self, noise_interval: impl Into<Animated<Duration>>) -> Rain {
+    self.noise_interval = noise_interval.into();
     self
   }
 
@@ -530,7 +2147,7 @@ impl Rain {
   ///
   /// ```
   /// use std::time::Duration;
-  /// use tui_rain::{CharacterSet, Rain};
+  /// use tui_rain_cli::{CharacterSet, Rain};
   ///
   /// let elapsed = Duration::from_secs(5);
   ///
@@ -544,7 +2161,7 @@ impl Rain {
   ///
   /// ```
   /// use std::time::Duration;
-  /// use tui_rain::{CharacterSet, Rain};
+  /// use tui_rain_cli::{CharacterSet, Rain};
   ///
   /// let elapsed = Duration::from_secs(5);
   ///
@@ -560,6 +2177,20 @@ impl Rain {
   /// - `CharacterSet::HalfKana` is the half-width Japanese kana character set (used
   ///   in the classic matrix rain)
   /// - `CharacterSet::Lowercase` is the lowercase English character set
+  ///
+  /// To display text in order instead of random noise, provide a sequence:
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{CharacterSet, Rain};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Rain::new_matrix(elapsed)
+  ///     .with_character_set(CharacterSet::Sequence {
+  ///         chars: "GET /index.html 200".chars().collect(),
+  ///     });
+  /// ```
   pub fn with_character_set(mut self, character_set: CharacterSet) -> Rain {
     self.character_set = character_set;
     self
@@ -570,60 +2201,380 @@ impl Rain {
     Pcg64Mcg::seed_from_u64(self.seed)
   }
 
-  fn build_message(&self, width: u16, height: u16) -> (Vec<Message>, String) {
-    let elapsed = self.elapsed.as_secs_f64();
-    let message_speed = self.message_speed.speed();
-    let mut messages: Vec<Message> = vec![];
-    let chunks: Vec<Vec<char>> = if self.message.is_empty() {
-      vec![]
-    } else {
-      split_into_chunks(self.message.as_str(), (width - 2) as usize)
+  /// Find the playlist entry shown at `elapsed`, and how long it has been shown for.
+  fn current_playlist_entry(&self, elapsed: Duration) -> Option<(&PlaylistEntry, Duration)> {
+    let total: Duration = self.message_playlist.iter().map(|entry| entry.duration).sum();
+    if total.is_zero() {
+      return None;
+    }
+    let mut offset = Duration::from_nanos((elapsed.as_nanos() % total.as_nanos()) as u64);
+    for entry in &self.message_playlist {
+      if offset < entry.duration {
+        return Some((entry, offset));
+      }
+      offset -= entry.duration;
+    }
+    None
+  }
+
+  fn build_message(&self, width: u16, height: u16) -> MessageLayout<'_> {
+    let elapsed = self.elapsed.saturating_sub(self.message_start);
+    let (spec, elapsed) = match self.current_playlist_entry(elapsed) {
+      Some((entry, entry_elapsed)) => (
+        MessageSpec {
+          text: &entry.text,
+          color: entry.color.unwrap_or(self.message_color),
+          speed: entry.speed.unwrap_or(self.message_speed),
+          effect: entry.effect.unwrap_or(self.message_effect),
+        },
+        entry_elapsed,
+      ),
+      None => (
+        MessageSpec {
+          text: &self.message,
+          color: self.message_color,
+          speed: self.message_speed,
+          effect: self.message_effect,
+        },
+        elapsed,
+      ),
+    };
+    self.layout_message(spec, elapsed.as_secs_f64(), width, height)
+  }
+
+  fn layout_message<'a>(&self, spec: MessageSpec<'a>, elapsed: f64, width: u16, height: u16) -> MessageLayout<'a> {
+    let message_speed = spec.speed.speed();
+    let mut cells: Vec<MessageCell> = vec![];
+
+    // Wrap the message to the space left inside the padding.
+    let padding = self.message_padding;
+    let inner_width = width.saturating_sub(padding.left.saturating_add(padding.right));
+    let inner_height = height.saturating_sub(padding.top.saturating_add(padding.bottom));
+    // Without color, the message stands out from the rain in bold instead.
+    let base_style = match self.capabilities.colors {
+      ColorSupport::Monochrome => Style::default().bold(),
+      _ => Style::default().fg(spec.color),
+    }
+    .patch(spec.text.style);
+    let lines = spec
+      .text
+      .lines
+      .iter()
+      .map(|line| line.styled_graphemes(base_style).filter(|g| !g.symbol.chars().any(char::is_control)).collect())
+      .collect();
+    let lines = wrap_lines(lines, inner_width as usize, inner_height as usize);
+    let block_width = lines.iter().map(|line| line_width(line)).max().unwrap_or(0) as i32;
+    let block_height = lines.len() as i32;
+
+    // Place the block of lines at its final position.
+    let (percent_x, percent_y) = self.message_position.percentages();
+    let final_x = padding.left as i32 + (inner_width as i32 - block_width).max(0) * percent_x as i32 / 100;
+    let final_y = padding.top as i32 + (inner_height as i32 - block_height).max(0) * percent_y as i32 / 100;
+
+    // While dropping in, the block is offset towards its entry edge by the distance it
+    // has yet to travel.
+    let travelled = (elapsed * message_speed) as i32;
+    let (offset_x, offset_y) = match (spec.effect, self.message_edge) {
+      (MessageEffect::DropIn, MessageEdge::Top) => (0, -(final_y + block_height - travelled).max(0)),
+      (MessageEffect::DropIn, MessageEdge::Bottom) => (0, (height as i32 - final_y - travelled).max(0)),
+      (MessageEffect::DropIn, MessageEdge::Left) => (-(final_x + block_width - travelled).max(0), 0),
+      (MessageEffect::DropIn, MessageEdge::Right) => ((width as i32 - final_x - travelled).max(0), 0),
+      _ => (0, 0),
     };
-    let message_len = chunks.len() as i16;
-    let initial_cycle_offset_secs = -message_len as f64 / message_speed;
-    let cycle_time_secs = (height / 2 + message_len as u16 + (message_len / 2) as u16) as f64 / message_speed;
-    let current_cycle_offset_secs = elapsed + initial_cycle_offset_secs - (message_len as f64 / 2.0);
-    let head_y = (current_cycle_offset_secs * message_speed) as i16;
 
     let debug = format!(
-      "mgs_len:{} offset:{} cycle_time:{} head_y:{} speed:{} current_offset:{:.2} elapsed:{:.2}",
-      message_len, initial_cycle_offset_secs, cycle_time_secs, head_y, message_speed, current_cycle_offset_secs, elapsed
+      "block:{}x{} pos:{},{} offset:{},{} speed:{} elapsed:{:.2}",
+      block_width, block_height, final_x, final_y, offset_x, offset_y, message_speed, elapsed
     );
 
-    for i in 0..chunks.len() {
-      let line = &chunks[i];
-      let x = (width - (line.len()) as u16) / 2;
+    // The backdrop covers the message block and its margin, plus the border if any.
+    let margin = self.message_backdrop_margin;
+    let border = matches!(self.message_backdrop, MessageBackdrop::Border { .. }) as i32;
+    let backdrop = if self.message_backdrop == MessageBackdrop::None || block_height == 0 {
+      None
+    } else {
+      let left = final_x + offset_x - margin.left as i32 - border;
+      let top = final_y + offset_y - margin.top as i32 - border;
+      let right = final_x + offset_x + block_width + margin.right as i32 + border;
+      let bottom = final_y + offset_y + block_height + margin.bottom as i32 + border;
+      let (left, top) = (left.max(0), top.max(0));
+      let (right, bottom) = (right.min(width as i32), bottom.min(height as i32));
+      (left < right && top < bottom).then(|| Rect::new(left as u16, top as u16, (right - left) as u16, (bottom - top) as u16))
+    };
 
-      let y: i16 = if elapsed > cycle_time_secs {
-        height as i16 / 2 as i16 + i as i16 - (message_len / 2)
-      } else {
-        (head_y) + i as i16
+    // Blinking messages are hidden for the second half of every second.
+    if spec.effect == MessageEffect::Blink && elapsed.fract() >= 0.5 {
+      return MessageLayout {
+        cells,
+        backdrop,
+        color: spec.color,
+        effect: spec.effect,
+        shown_since: self.elapsed.as_secs_f64() - elapsed,
+        debug,
       };
+    }
 
-      let style = Style::default().fg(self.message_color);
-      if y < 0 || y >= height as i16 {
+    for (i, line) in lines.into_iter().enumerate() {
+      let free = block_width - line_width(&line) as i32;
+      let align_x = match self.message_alignment {
+        MessageAlignment::Left => 0,
+        MessageAlignment::Center => free / 2,
+        MessageAlignment::Right => free,
+      };
+      let y = final_y + offset_y + i as i32;
+      if y < 0 || y >= height as i32 {
         continue;
       }
-      let message = Message {
-        x,
-        y: y as u16,
-        content: line.clone(),
-        style,
+
+      // Only keep graphemes that are entirely on screen, since the line may be
+      // partially scrolled in.
+      let mut x = final_x + offset_x + align_x;
+      for grapheme in line {
+        let grapheme_width = grapheme.symbol.width() as i32;
+        if x >= 0 && x + grapheme_width <= width as i32 {
+          cells.push(MessageCell {
+            x: x as u16,
+            y: y as u16,
+            width: grapheme_width as u16,
+            grapheme,
+          });
+        }
+        x += grapheme_width;
+      }
+    }
+    MessageLayout {
+      cells,
+      backdrop,
+      color: spec.color,
+      effect: spec.effect,
+      shown_since: self.elapsed.as_secs_f64() - elapsed,
+      debug,
+    }
+  }
+
+  /// Find when each message cell was decoded by a drop head passing over it, for the
+  /// decode effect. Cells that are still hidden are `None`.
+  ///
+  /// Drops follow a closed-form path, so this looks at every cycle of every drop since
+  /// the message appeared rather than keeping any state between frames.
+  ///
+  /// Reveal times are in [drop time](Rain::drop_time).
+  fn decode_reveals(&self, message: &MessageLayout, entropy: &[Vec<u64>], width: u16, height: u16) -> Vec<Option<f64>> {
+    let timeout = self.decode_timeout.as_secs_f64();
+    if self.elapsed.as_secs_f64() - message.shown_since >= timeout {
+      let decoded = self.drop_time(Duration::from_secs_f64(message.shown_since + timeout));
+      return vec![Some(decoded); message.cells.len()];
+    }
+    let elapsed = self.drop_time(self.elapsed);
+    let start = self.drop_time(Duration::from_secs_f64(message.shown_since.max(0.0)));
+
+    // Whitespace has nothing to decode.
+    let mut reveals: Vec<Option<f64>> = message.cells.iter().map(|cell| is_whitespace(&cell.grapheme).then_some(start)).collect();
+
+    // Find where each cell is along and across the drop tracks.
+    let axes = self.direction.axes(width, height);
+    let track_cells: Vec<Vec<(u16, u16)>> = message
+      .cells
+      .iter()
+      .map(|cell| (cell.x..cell.x + cell.width).map(|x| axes.locate(x, cell.y)).collect())
+      .collect();
+
+    for (index, drop_entropy) in entropy.iter().enumerate().filter(|(_, drop_entropy)| !drop_entropy.is_empty()) {
+      let motion = self.drop_motion(drop_entropy);
+      let offset = motion.initial_cycle_offset_secs;
+
+      // Cycle 0 is never rendered, so it can't decode anything either.
+      let first_cycle = (((start + offset) / motion.cycle_time_secs) as usize).max(1);
+      let last_cycle = ((elapsed + offset) / motion.cycle_time_secs) as usize;
+      for cycle in first_cycle..=last_cycle {
+        let x_entropy = drop_entropy[cycle % drop_entropy.len()];
+        let cycle_start = cycle as f64 * motion.cycle_time_secs - offset;
+        if !self.drop_falls(index, x_entropy, cycle_start, width, height) {
+          continue;
+        }
+        let stop = self.stream_stop(x_entropy, axes.along);
+        for (cells, reveal) in track_cells.iter().zip(reveals.iter_mut()) {
+          for &(across, along) in cells {
+            // The time at which the head reaches the cell along the track during this cycle.
+            let passed = cycle_start + motion.time_at(along as f64);
+            if self.drop_column(index, x_entropy, passed, axes.across) != across || stop.is_some_and(|stop| along > stop) {
+              continue;
+            }
+            if passed >= start && passed <= elapsed && reveal.is_none_or(|reveal| passed < reveal) {
+              *reveal = Some(passed);
+            }
+          }
+        }
+      }
+    }
+    reveals
+  }
+
+  /// Compute how a drop moves along its track, given its non-empty entropy vec.
+  fn drop_motion(&self, entropy: &[u64]) -> DropMotion {
+    let rain_speed = self.rain_speed.value_at(self.elapsed).speed();
+
+    // Use some entropy to compute the drop's actual speed.
+    // n.b. since the entropy vec is stable, the drop's speed will not vary over time.
+    let speed = match self.speed_distribution {
+      Distribution::Uniform => uniform(
+        entropy[0],
+        rain_speed * (1.0 - self.rain_speed_variance),
+        rain_speed * (1.0 + self.rain_speed_variance),
+      ),
+      distribution => rain_speed * distribution.sample(uniform(entropy[0], 0.0, 1.0), self.rain_speed_variance),
+    }
+    .max(1e-3); // Prevent speed from hitting 0 (if user specifies high variance)
+
+    let mut motion = DropMotion {
+      speed,
+      gravity: self.gravity.filter(|gravity| *gravity > 0.0),
+      cycle_time_secs: 0.0,
+      initial_cycle_offset_secs: 0.0,
+    };
+
+    // Compute how long our drop will take to make 1 cycle given our track len and speed
+    motion.cycle_time_secs = motion.time_at(entropy.len() as f64);
+
+    // Use some entropy to compute a stable random time offset for this drop.
+    // If this value were 0, every drop would start falling with an identical y value.
+    motion.initial_cycle_offset_secs = uniform(entropy[0], 0.0, motion.cycle_time_secs);
+    motion
+  }
+
+  /// Get the position across the tracks of a drop during the cycle with the given
+  /// entropy, at `time` in [drop time](Rain::drop_time). Tumbling drops sway around it.
+  fn drop_x(&self, x_entropy: u64, time: f64, width: u16) -> u16 {
+    let x = (x_entropy % width as u64) as i64;
+    let Some(tumble) = &self.tumble else {
+      return x as u16;
+    };
+    let offset = self.sway(tumble, x_entropy, time).round() as i64;
+    (x + offset).rem_euclid(width as i64) as u16
+  }
+
+  /// Get how far a tumbling drop has swayed from its position across the tracks.
+  fn sway(&self, tumble: &Tumble, x_entropy: u64, time: f64) -> f64 {
+    let period = (tumble.sway_period.as_secs_f64() * uniform(x_entropy.rotate_left(13), 0.8, 1.2)).max(1e-3);
+    let phase = uniform(x_entropy.rotate_left(7), 0.0, TAU);
+    tumble.sway * (TAU * time / period + phase).sin()
+  }
+
+  /// Get where within its cell a drop is across the tracks, from 0 to 1, for drawing
+  /// it at a finer [resolution](Rain::with_resolution).
+  fn drop_across(&self, x_entropy: u64, time: f64) -> f64 {
+    match (&self.column_lock, &self.tumble) {
+      (Some(_), _) => 0.0,
+      (None, Some(tumble)) => {
+        let sway = self.sway(tumble, x_entropy, time);
+        sway - sway.round() + 0.5
+      }
+      (None, None) => uniform(x_entropy.rotate_left(23), 0.0, 1.0),
+    }
+  }
+
+  /// Get the position across the tracks of a drop, which is fixed for
+  /// [column locked](Rain::with_column_lock) rain.
+  fn drop_column(&self, index: usize, x_entropy: u64, time: f64, across: u16) -> u16 {
+    match &self.column_lock {
+      Some(column_lock) => index as u16 * column_lock.spacing.max(1),
+      None => self.drop_x(x_entropy, time, across),
+    }
+  }
+
+  /// Get the number of columns of [column locked](Rain::with_column_lock) rain.
+  fn num_columns(&self, across: u16) -> usize {
+    self
+      .column_lock
+      .as_ref()
+      .map_or(0, |column_lock| across.div_ceil(column_lock.spacing.max(1)) as usize)
+  }
+
+  /// Get where a [column locked](Rain::with_column_lock) stream stops during the
+  /// cycle with the given entropy, or `None` if it runs its whole track.
+  fn stream_stop(&self, x_entropy: u64, along: u16) -> Option<u16> {
+    let column_lock = self.column_lock.as_ref()?;
+    (uniform(x_entropy.rotate_left(53), 0.0, 1.0) < column_lock.stop_chance)
+      .then(|| uniform(x_entropy.rotate_left(59), along as f64 / 3.0, along as f64) as u16)
+  }
+
+  /// Get the time drops are placed at, for the rain at `elapsed`.
+  ///
+  /// Drop positions only depend on how far the rain has fallen. While the speed is
+  /// animated, this is the distance fallen so far divided by the current speed, so the
+  /// drops keep falling smoothly instead of jumping whenever the speed changes.
+  fn drop_time(&self, elapsed: Duration) -> f64 {
+    if self.rain_speed.is_constant() {
+      return elapsed.as_secs_f64();
+    }
+    let speed = self.rain_speed.value_at(self.elapsed).speed().max(1e-3);
+    self.rain_speed.integrate(elapsed, RainSpeed::speed) / speed
+  }
+
+  /// Get the elapsed time at which the rain reached a [drop time](Rain::drop_time).
+  fn wall_time(&self, drop_time: f64) -> f64 {
+    if self.rain_speed.is_constant() {
+      return drop_time;
+    }
+    let speed = self.rain_speed.value_at(self.elapsed).speed().max(1e-3);
+    let distance = drop_time * speed;
+    let speed_of = |speed: &RainSpeed| speed.speed().max(0.0);
+    self.rain_speed.time_to_reach(distance, speed_of).clamp(0.0, self.elapsed.as_secs_f64())
+  }
+
+  /// Get the target number of drops at `elapsed`, blending while the density changes.
+  fn num_drops(&self, elapsed: Duration, area: Rect) -> usize {
+    let (from, to, t) = self.rain_density.segment(elapsed);
+    (from.num_drops(area) as f64).interpolate(&(to.num_drops(area) as f64), t).round() as usize
+  }
+
+  /// Get the highest target number of drops the density ever reaches.
+  fn max_drops(&self, area: Rect) -> usize {
+    self
+      .rain_density
+      .keyframes
+      .iter()
+      .map(|keyframe| keyframe.value.num_drops(area))
+      .max()
+      .unwrap_or(0)
+  }
+
+  /// Whether the drop with the given index falls during the cycle starting at
+  /// `cycle_start`, in [drop time](Rain::drop_time).
+  ///
+  /// While the density changes, drops are only added or removed at the start of a
+  /// cycle, so they never appear or vanish in the middle of the screen.
+  ///
+  /// Columns of [column locked](Rain::with_column_lock) rain are active at random
+  /// instead, so the density sets how often they are.
+  fn drop_falls(&self, index: usize, x_entropy: u64, cycle_start: f64, width: u16, height: u16) -> bool {
+    if self.column_lock.is_some() {
+      let started = match self.rain_density.is_constant() {
+        true => self.elapsed,
+        false => Duration::try_from_secs_f64(self.wall_time(cycle_start)).unwrap_or_default(),
       };
-      messages.push(message);
+      let columns = self.num_columns(self.direction.axes(width, height).across).max(1);
+      // Drops are on the screen for about half their cycle, so activate twice as many.
+      let chance = 2.0 * self.num_drops(started, Rect::new(0, 0, width, height)) as f64 / columns as f64;
+      return uniform(x_entropy.rotate_left(41), 0.0, 1.0) < chance;
+    }
+    if self.rain_density.is_constant() {
+      return true;
     }
-    (messages, debug)
+    let started = Duration::try_from_secs_f64(self.wall_time(cycle_start)).unwrap_or_default();
+    index < self.num_drops(started, Rect::new(0, 0, width, height)) * 2
   }
 
   /// Build a drop from the given consistent initial entropy state.
   ///
   /// The entropy vector's length becomes the drop's track length, so ensure it's at
-  /// least the window height.
-  fn build_drop(&self, entropy: Vec<u64>, width: u16, height: u16) -> Vec<Glyph> {
-    let elapsed = self.elapsed.as_secs_f64();
-    let rain_speed = self.rain_speed.speed();
-    let tail_lifespan = self.tail_lifespan.as_secs_f64();
-    let noise_interval = self.noise_interval.as_secs_f64();
+  /// least the length of the screen along the [fall direction](Rain::with_direction).
+  fn build_drop(&self, index: usize, entropy: &[u64], width: u16, height: u16) -> Vec<Glyph> {
+    let elapsed = self.drop_time(self.elapsed);
+    let axes = self.direction.axes(width, height);
+    let tail_lifespan = self.tail_lifespan.value_at(self.elapsed).as_secs_f64();
+    let noise_interval = self.noise_interval.value_at(self.elapsed).as_secs_f64();
+    let color = self.color.value_at(self.elapsed);
 
     // A single drop can expect to be called with the exact same entropy vec on each
     // frame. This means we can sample the entropy vec to reproducibly generate
@@ -638,35 +2589,77 @@ impl Rain {
     // This track is usually longer than the screen height by a random amount.
     let track_len = entropy.len() as u16;
 
-    // Use some entropy to compute the drop's actual speed.
-    // n.b. since the entropy vec is stable, the drop's speed will not vary over time.
-    let rain_speed = uniform(
-      entropy[0],
-      rain_speed * (1.0 - self.rain_speed_variance),
-      rain_speed * (1.0 + self.rain_speed_variance),
-    )
-    .max(1e-3); // Prevent speed from hitting 0 (if user specifies high variance)
-
-    // Compute how long our drop will take to make 1 cycle given our track len and speed
-    let cycle_time_secs = entropy.len() as f64 / rain_speed;
-
-    // Use some entropy to compute a stable random time offset for this drop.
-    // If this value were 0, every drop would start falling with an identical y value.
-    let initial_cycle_offset_secs = uniform(entropy[0], 0.0, cycle_time_secs);
+    // Use some entropy to compute the drop's speed and timing.
+    let motion = self.drop_motion(entropy);
+    let (cycle_time_secs, initial_cycle_offset_secs) = (motion.cycle_time_secs, motion.initial_cycle_offset_secs);
 
     // Compute how far we are into the current cycle and current drop head height.
     let current_cycle_offset_secs = (elapsed + initial_cycle_offset_secs) % cycle_time_secs;
-    let head_y = (current_cycle_offset_secs * rain_speed) as u16;
+    let head_y = (motion.distance(current_cycle_offset_secs) as u16).min(track_len - 1);
+
+    // Column locked streams may stop partway down their track during a cycle.
+    let current_cycle = ((elapsed + initial_cycle_offset_secs) / cycle_time_secs) as usize;
+    let stop = |cycle: usize| self.stream_stop(entropy[cycle % entropy.len()], axes.along);
+    let stopped = stop(current_cycle).filter(|stop| head_y > *stop);
+    let head_y = stopped.unwrap_or(head_y);
+
+    // Where exactly the head is, for drawing the drop at a finer resolution.
+    let head_position = match stopped {
+      Some(stop) => stop as f64 + 1.0,
+      None => motion.distance(current_cycle_offset_secs).min(track_len as f64),
+    };
+
+    // Draw how this drop differs from the others. With depth correlation, the tail and
+    // brightness use the same quantile as the speed, so slow drops look further away.
+    let quantile = |rotation: u32| match self.depth_correlation {
+      true => uniform(entropy[0], 0.0, 1.0),
+      false => uniform(entropy[0].rotate_left(rotation), 0.0, 1.0),
+    };
+    let tail_lifespan = tail_lifespan * self.tail_variation.sample(quantile(21)).max(0.0);
+    let brightness = self.brightness_variation.sample(quantile(37)).max(0.0);
+    let head_color = match self.head_color_variation.variance {
+      0.0 => self.head_color,
+      _ => {
+        let mix = self.head_color_variation.sample(uniform(entropy[0].rotate_left(49), 0.0, 1.0)) - 1.0;
+        self.head_color.interpolate(&color, mix.abs().min(1.0))
+      }
+    };
 
-    // Compute drop length given speed and tail lifespan.
+    // Compute drop length given the current speed and tail lifespan, so drops
+    // accelerating under gravity grow longer as they speed up.
     // Cap at screen height to avoid weird wraparound when tail length is long.
-    let drop_len = ((rain_speed * tail_lifespan) as u16).min(height);
+    let speed = match stopped {
+      Some(_) => motion.speed,
+      None => motion.speed_at(current_cycle_offset_secs),
+    };
+    let drop_len = ((speed * tail_lifespan) as u16).min(axes.along);
+    let tail_position = head_position - drop_len.saturating_sub(1) as f64;
 
     // Render each glyph in the drop.
+    let mut last_cycle: Option<(usize, bool)> = None;
     (0..drop_len)
       .filter_map(|y_offset| {
-        // Compute how long ago this glyph would have first appeared
-        let age = y_offset as f64 / rain_speed;
+        // Compute how long ago this glyph would have first appeared. Glyphs behind the
+        // start of the track were passed during the previous cycle.
+        // A stopped head stays put while the glyphs behind it keep aging.
+        let head_time = match stopped {
+          Some(_) => current_cycle_offset_secs,
+          None => motion.time_at(head_y as f64),
+        };
+        let age = match (motion.gravity, stopped) {
+          (None, None) => y_offset as f64 / motion.speed,
+          _ if y_offset <= head_y => head_time - motion.time_at((head_y - y_offset) as f64),
+          _ => head_time + cycle_time_secs - motion.time_at((head_y + track_len - y_offset) as f64),
+        };
+
+        // Stopped streams erase from the top as their oldest glyphs expire, and never
+        // reach past where they stopped.
+        if stopped.is_some() && age > tail_lifespan {
+          return None;
+        }
+        if y_offset > head_y && stop(current_cycle.wrapping_sub(1)).is_some_and(|stop| head_y + track_len - y_offset > stop) {
+          return None;
+        }
 
         // If it would have first appeared before the rendering began, don't render.
         if age > elapsed {
@@ -682,33 +2675,78 @@ impl Rain {
           return None;
         }
 
+        // Skip cycles this drop doesn't fall in while the density changes. Glyphs of
+        // the same cycle are next to each other, so remember the last answer.
+        let falls = match last_cycle {
+          Some((cycle, falls)) if cycle == cycle_num => falls,
+          _ => {
+            let cycle_start = cycle_num as f64 * cycle_time_secs - initial_cycle_offset_secs;
+            self.drop_falls(index, entropy[cycle_num % entropy.len()], cycle_start, width, height)
+          }
+        };
+        last_cycle = Some((cycle_num, falls));
+        if !falls {
+          return None;
+        }
+
         // Get stable entropy to decide what column cycle X is rendered in.
         // This must be per-glyph to prevent drops from jumping side-to-side when they wrap around.
         let x_entropy = entropy[cycle_num % entropy.len()];
-        let x = (x_entropy % width as u64) as u16;
+        let x = self.drop_column(index, x_entropy, elapsed - age, axes.across);
 
         // Compute the y value for this glyph, and don't render if off the screen.
         let y = (head_y + track_len - y_offset) % track_len;
-        if y >= height {
+        if y >= axes.along {
           return None;
         }
 
+        // The part of the cell the drop covers, which only matters at a finer resolution.
+        let cell_start = head_y as f64 - y_offset as f64;
+        let span = ((tail_position - cell_start).clamp(0.0, 1.0), (head_position - cell_start).clamp(0.0, 1.0));
+        let across = self.drop_across(x_entropy, elapsed - age);
+
         // The 'noise' of glyphs randomly changing is actually modeled as every glyph in the track
         // just cycling through possible values veeeery slowly. We need a random offset for this
         // cycling so every glyph doesn't change at the same time.
         let time_offset = uniform(entropy[y as usize], 0.0, noise_interval * self.character_set.size() as f64);
 
-        // Decide what character is rendered based on noise.
-        let content = self.character_set.get(((time_offset + elapsed) / noise_interval) as u32);
+        // Decide what character is rendered based on noise, or read the sequence in order
+        // starting from a stable per-cycle position.
+        let mut content = match (&self.character_set, &self.tumble) {
+          (CharacterSet::Sequence { chars }, _) if !chars.is_empty() => chars[((x_entropy % chars.len() as u64) as usize + y as usize) % chars.len()],
+          // Tumbling drops turn through the glyphs in order, each at its own pace.
+          (character_set, Some(tumble)) => {
+            let spin_interval = (tumble.spin_interval.as_secs_f64() * uniform(x_entropy.rotate_left(29), 0.7, 1.3)).max(1e-3);
+            let turns = ((elapsed - age) / spin_interval) as u32;
+            character_set.get((x_entropy % character_set.size().max(1) as u64) as u32 + turns)
+          }
+          (character_set, None) => character_set.get(((time_offset + elapsed) / noise_interval) as u32),
+        };
+
+        // Place the glyph on the screen, turning the track to the fall direction.
+        let (x, y) = axes.place(x, y);
 
         // Compute the styling for the glyph
         let mut style = Style::default();
 
         // Color appropriately depending on whether this glyph is the head.
-        if age > 0.0 {
-          style = style.fg(self.color)
+        let fg = if !self.color_palette.is_empty() {
+          self.color_palette[(x_entropy.rotate_left(17) % self.color_palette.len() as u64) as usize]
+        } else if age > 0.0 {
+          color
         } else {
-          style = style.fg(self.head_color)
+          head_color
+        };
+        style = style.fg(shade(fg, brightness));
+
+        // Shape the rain with the mask, or tint the glyphs passing over it.
+        // This doesn't consume any entropy, so the rest of the rain is unaffected.
+        if let Some(mask) = &self.mask {
+          match (self.mask_mode, mask.get(x, y, width, height)) {
+            (MaskMode::Clip, None) | (MaskMode::Cutout, Some(_)) => return None,
+            (MaskMode::Tint, Some(color)) if age > 0.0 => style = style.fg(color),
+            _ => {}
+          }
         }
 
         // The lowest third of glyphs is bold, the highest third is dim
@@ -722,7 +2760,38 @@ impl Rain {
           }
         }
 
-        Some(Glyph { x, y, age, content, style })
+        // Column locked streams flash white as they spawn, like the original.
+        if let Some(column_lock) = &self.column_lock {
+          if age == 0.0 && current_cycle_offset_secs < column_lock.head_flash.as_secs_f64() {
+            style = style.fg(Color::White).bold().not_dim();
+          }
+        }
+
+        // Without color, heads with a tail stand out in reverse video, drops further
+        // away are dim, and the end of tails fades out to dots.
+        if self.capabilities.colors == ColorSupport::Monochrome {
+          let fading = y_offset > drop_len * 2 / 3;
+          style = match age {
+            0.0 if drop_len > 1 => Style::default().reversed().bold(),
+            0.0 => Style::default().bold(),
+            _ if fading || brightness < 0.8 => Style::default().dim(),
+            _ if y_offset < drop_len / 3 => Style::default().bold(),
+            _ => Style::default(),
+          };
+          if fading && drop_len >= 3 && self.tumble.is_none() && !matches!(self.character_set, CharacterSet::Sequence { .. }) {
+            content = '.';
+          }
+        }
+
+        Some(Glyph {
+          x,
+          y,
+          age,
+          content,
+          style,
+          span,
+          across,
+        })
       })
       .collect()
   }
@@ -730,27 +2799,55 @@ impl Rain {
 
 impl Widget for Rain {
   fn render(self, area: Rect, buf: &mut Buffer) {
+    self.draw(area, buf, None);
+  }
+}
+
+impl Rain {
+  /// Render the rain, drawing the drops on `canvas` in pixels instead of in cells
+  /// wherever it covers them.
+  fn draw(mut self, area: Rect, buf: &mut Buffer, mut canvas: Option<&mut Canvas>) {
+    // Nothing fits in an empty area, e.g. while the terminal is being resized.
+    if area.is_empty() {
+      return;
+    }
+
+    // Terminals without unicode get ASCII glyphs, in whole cells.
+    if !self.capabilities.unicode {
+      if let Some(ascii) = self.character_set.ascii() {
+        self.character_set = ascii;
+      }
+      self.resolution = Resolution::Cell;
+    }
+
     let mut rng = self.build_rng();
 
     // We don't actually have n drops with tracks equal to the screen height.
     // We actually have 2n drops with tracks ranging from 1.5 to 2.5 the screen height.
     // This introduces more randomness to the apparent n and reduces cyclic appearance.
-    let num_drops = self.rain_density.num_drops(area) * 2;
-    let drop_track_lens: Vec<usize> = (0..num_drops)
-      .map(|_| (area.height as u64 * 3 / 2 + rng.next_u64() % area.height as u64) as usize)
-      .collect();
+    // Column locked rain has a drop per column instead.
+    let num_drops = match self.column_lock {
+      Some(_) => self.num_columns(self.direction.axes(area.width, area.height).across),
+      None => self.max_drops(area) * 2,
+    };
 
     // We construct entropy consistently every frame to mimic statefulness.
     // This is not a performance bottleneck, so caching wouldn't deliver much benefit.
-    let entropy: Vec<Vec<u64>> = drop_track_lens
-      .iter()
-      .map(|track_len| (0..*track_len).map(|_| rng.next_u64()).collect())
+    // Each drop's entropy is drawn in turn, so changing the density only adds or
+    // removes drops at the end instead of reshuffling all of them.
+    let entropy: Vec<Vec<u64>> = (0..num_drops)
+      .map(|_| {
+        let along = self.direction.axes(area.width, area.height).along as u64;
+        let track_len = along * 3 / 2 + rng.next_u64() % along;
+        (0..track_len).map(|_| rng.next_u64()).collect()
+      })
       .collect();
 
     // For every entropy vec, construct a single drop (vertical line of glyphs).
     let mut glyphs: Vec<Glyph> = entropy
-      .into_iter()
-      .flat_map(|drop_entropy| self.build_drop(drop_entropy, area.width, area.height))
+      .iter()
+      .enumerate()
+      .flat_map(|(index, drop_entropy)| self.build_drop(index, drop_entropy, area.width, area.height))
       .collect();
 
     // Sort all the glyphs by age so drop heads always render on top.
@@ -758,30 +2855,194 @@ impl Widget for Rain {
     glyphs.sort_by(|a, b| a.age.partial_cmp(&b.age).unwrap_or(Ordering::Equal));
 
     // Render the message in the center of the screen.
-    let (messages, debug) = self.build_message(area.width, area.height);
+    let message = self.build_message(area.width, area.height);
+
+    let axes = self.direction.axes(area.width, area.height);
+
+    // At a finer resolution, the dots of all glyphs in a cell are combined.
+    let mut dots: Vec<Option<(u8, Style)>> = vec![None; area.width as usize * area.height as usize];
 
     //buf.reset();
     // Actually render to the buffer.
     for glyph in glyphs {
-      buf[(glyph.x, glyph.y)].set_char(glyph.content);
-      buf[(glyph.x, glyph.y)].set_style(glyph.style);
+      let mut style = glyph.style;
+      if let Some(backdrop) = message.backdrop {
+        let covered = backdrop.contains((glyph.x, glyph.y).into());
+        // Drops stop at the backdrop, so nothing falls in its shadow along the tracks.
+        let sheltered = match (axes.horizontal, axes.reversed) {
+          (false, false) => glyph.x >= backdrop.left() && glyph.x < backdrop.right() && glyph.y >= backdrop.top(),
+          (false, true) => glyph.x >= backdrop.left() && glyph.x < backdrop.right() && glyph.y < backdrop.bottom(),
+          (true, false) => glyph.y >= backdrop.top() && glyph.y < backdrop.bottom() && glyph.x >= backdrop.left(),
+          (true, true) => glyph.y >= backdrop.top() && glyph.y < backdrop.bottom() && glyph.x < backdrop.right(),
+        };
+        match (self.backdrop_drops, self.message_backdrop) {
+          (BackdropDrops::Stop, _) if sheltered => continue,
+          (BackdropDrops::PassBehind, MessageBackdrop::Dim) if covered => style = style.dim().not_bold(),
+          (BackdropDrops::PassBehind, _) if covered => continue,
+          _ => {}
+        }
+      }
+      if let Some(canvas) = canvas.as_deref_mut().filter(|canvas| canvas.covers(area.x + glyph.x, area.y + glyph.y)) {
+        canvas.draw_streak(area.x + glyph.x, area.y + glyph.y, axes, glyph.span, glyph.across, style);
+        continue;
+      }
+      if self.resolution == Resolution::Cell {
+        buf[(area.x + glyph.x, area.y + glyph.y)].set_char(glyph.content);
+        buf[(area.x + glyph.x, area.y + glyph.y)].set_style(style);
+        continue;
+      }
+      // Like glyphs, the last one drawn in a cell sets its style.
+      let cell = &mut dots[glyph.y as usize * area.width as usize + glyph.x as usize];
+      let covered = cell.map_or(0, |(covered, _)| covered);
+      *cell = Some((covered | self.resolution.dots(axes, glyph.span, glyph.across), style));
+    }
+    for (i, cell) in dots.into_iter().enumerate() {
+      if let Some((dots, style)) = cell {
+        let (x, y) = (area.x + (i % area.width as usize) as u16, area.y + (i / area.width as usize) as u16);
+        buf[(x, y)].set_char(self.resolution.symbol(dots));
+        buf[(x, y)].set_style(style);
+      }
+    }
+
+    if let Some(backdrop) = message.backdrop {
+      let backdrop = Rect::new(area.x + backdrop.x, area.y + backdrop.y, backdrop.width, backdrop.height);
+      match self.message_backdrop {
+        MessageBackdrop::Solid { color } => buf.set_style(backdrop, Style::default().bg(color)),
+        MessageBackdrop::Border { border_type } => Block::bordered()
+          .border_type(border_type)
+          .border_style(Style::default().fg(message.color))
+          .render(backdrop, buf),
+        _ => {}
+      }
     }
 
-    for message in messages {
-      for i in 0..(message.content.len() as u16) {
-        buf[(message.x + i, message.y)].set_char(message.content.get(i as usize).unwrap().clone());
-        buf[(message.x + i, message.y)].set_style(message.style);
+    // While decoding, hidden characters flicker through the character set like the
+    // rain, and decoded ones briefly flash in the head color, or in reverse video
+    // without color.
+    let monochrome = self.capabilities.colors == ColorSupport::Monochrome;
+    let elapsed = self.elapsed.as_secs_f64();
+    let drop_time = self.drop_time(self.elapsed);
+    let color = self.color.value_at(self.elapsed);
+    let reveals = (message.effect == MessageEffect::Decode).then(|| self.decode_reveals(&message, &entropy, area.width, area.height));
+    for (i, cell) in message.cells.into_iter().enumerate() {
+      match reveals.as_ref().map(|reveals| reveals[i]) {
+        Some(None) => {
+          let seed = (cell.x as u32).wrapping_mul(7919) ^ (cell.y as u32).wrapping_mul(104729);
+          let content = self.character_set.get(seed.wrapping_add((elapsed * DECODE_FLICKER_RATE) as u32));
+          buf[(area.x + cell.x, area.y + cell.y)].set_char(content);
+          buf[(area.x + cell.x, area.y + cell.y)].set_style(match monochrome {
+            true => Style::default().dim(),
+            false => Style::default().fg(color),
+          });
+        }
+        Some(Some(reveal)) if drop_time - reveal < DECODE_FLASH_SECS => {
+          buf[(area.x + cell.x, area.y + cell.y)].set_symbol(cell.grapheme.symbol);
+          buf[(area.x + cell.x, area.y + cell.y)].set_style(match monochrome {
+            true => cell.grapheme.style.reversed().bold(),
+            false => cell.grapheme.style.fg(self.head_color).bold(),
+          });
+        }
+        _ => {
+          buf[(area.x + cell.x, area.y + cell.y)].set_symbol(cell.grapheme.symbol);
+          buf[(area.x + cell.x, area.y + cell.y)].set_style(cell.grapheme.style);
+        }
+      }
+      // Wide graphemes cover the following cells, so clear whatever rain is under them.
+      for x in cell.x + 1..cell.x + cell.width {
+        buf[(area.x + x, area.y + cell.y)].reset();
       }
     }
 
     if self.verbose {
-      for i in 0..(debug.len()) {
-        buf[(i as u16, 0)].set_char(debug.chars().nth(i).unwrap());
+      for (i, c) in message.debug.chars().take(area.width as usize).enumerate() {
+        buf[(area.x + i as u16, area.y)].set_char(c);
       }
     }
   }
 }
 
+/// The axes of the screen that drop tracks run along and across.
+///
+/// Drops are laid out as if they fell straight down a screen `along` rows high and
+/// `across` columns wide, and then placed on the actual screen.
+#[derive(Copy, Clone, Debug)]
+struct FallAxes {
+  along: u16,
+  across: u16,
+  /// Whether tracks run along the width of the screen rather than its height.
+  horizontal: bool,
+  /// Whether tracks run up or left rather than down or right.
+  reversed: bool,
+  /// How many cells across drops move per cell along their track.
+  slant: f64,
+}
+
+impl FallAxes {
+  /// Place a position along and across the tracks on the screen.
+  fn place(self, across: u16, along: u16) -> (u16, u16) {
+    let along_pos = if self.reversed { self.along - 1 - along } else { along };
+    let across_pos = (across as i64 + (along as f64 * self.slant).round() as i64).rem_euclid(self.across as i64) as u16;
+    if self.horizontal {
+      (along_pos, across_pos)
+    } else {
+      (across_pos, along_pos)
+    }
+  }
+
+  /// Find the position along and across the tracks of a cell on the screen.
+  fn locate(self, x: u16, y: u16) -> (u16, u16) {
+    let (across_pos, along_pos) = if self.horizontal { (y, x) } else { (x, y) };
+    let along = if self.reversed { self.along - 1 - along_pos } else { along_pos };
+    let across = (across_pos as i64 - (along as f64 * self.slant).round() as i64).rem_euclid(self.across as i64) as u16;
+    (across, along)
+  }
+}
+
+/// How a single drop moves along its track.
+struct DropMotion {
+  /// Speed in pixels / second, or terminal velocity when falling under gravity.
+  speed: f64,
+  /// Acceleration in pixels / second², for drops that start at rest.
+  gravity: Option<f64>,
+  /// How long the drop takes to travel its whole track once.
+  cycle_time_secs: f64,
+  /// Stable random time offset, so drops don't all start at the top.
+  initial_cycle_offset_secs: f64,
+}
+
+impl DropMotion {
+  /// How long drops under gravity take to reach terminal velocity.
+  fn acceleration_secs(&self, gravity: f64) -> f64 {
+    self.speed / gravity
+  }
+
+  /// How far along its track the drop is, `t` seconds into a cycle.
+  fn distance(&self, t: f64) -> f64 {
+    match self.gravity {
+      Some(gravity) if t < self.acceleration_secs(gravity) => gravity * t * t / 2.0,
+      Some(gravity) => self.speed * (t - self.acceleration_secs(gravity) / 2.0),
+      None => self.speed * t,
+    }
+  }
+
+  /// How long into a cycle the drop reaches `distance` along its track.
+  fn time_at(&self, distance: f64) -> f64 {
+    match self.gravity {
+      Some(gravity) if distance < self.speed * self.acceleration_secs(gravity) / 2.0 => (2.0 * distance / gravity).sqrt(),
+      Some(gravity) => distance / self.speed + self.acceleration_secs(gravity) / 2.0,
+      None => distance / self.speed,
+    }
+  }
+
+  /// How fast the drop moves, `t` seconds into a cycle.
+  fn speed_at(&self, t: f64) -> f64 {
+    match self.gravity {
+      Some(gravity) => (gravity * t).min(self.speed),
+      None => self.speed,
+    }
+  }
+}
+
 /// A Glyph to be rendered on the screen.
 struct Glyph {
   x: u16,
@@ -789,15 +3050,38 @@ struct Glyph {
   age: f64,
   content: char,
   style: Style,
+  /// The part of the cell the drop covers along its track, from 0 at the back to 1 at
+  /// the front.
+  span: (f64, f64),
+  /// Where the drop is across the track within the cell, from 0 to 1.
+  across: f64,
 }
 
-/// A message to be rendered on the screen.
-#[derive(Clone, PartialEq, Debug)]
-struct Message {
+/// The resolved content and styling of the message currently shown.
+struct MessageSpec<'a> {
+  text: &'a Text<'static>,
+  color: Color,
+  speed: RainSpeed,
+  effect: MessageEffect,
+}
+
+/// The message laid out on the screen for the current frame.
+struct MessageLayout<'a> {
+  cells: Vec<MessageCell<'a>>,
+  backdrop: Option<Rect>,
+  color: Color,
+  effect: MessageEffect,
+  /// When the message appeared, in seconds on the same clock as `elapsed`.
+  shown_since: f64,
+  debug: String,
+}
+
+/// A grapheme of a message, placed on the screen.
+struct MessageCell<'a> {
   x: u16,
   y: u16,
-  content: Vec<char>,
-  style: Style,
+  width: u16,
+  grapheme: StyledGrapheme<'a>,
 }
 
 /// Map a uniform random u64 to a uniform random f64 in the range [lower, upper).
@@ -805,49 +3089,280 @@ fn uniform(seed: u64, lower: f64, upper: f64) -> f64 {
   (seed as f64 / u64::MAX as f64) * (upper - lower) + lower
 }
 
-/// Split a string into chunks of at most `max_length` characters.
-fn split_into_chunks(input: &str, max_length: usize) -> Vec<Vec<char>> {
-  if input.is_empty() {
-    vec![]
+/// Shade a color by a brightness factor, fading towards black below 1 and towards
+/// white above it.
+fn shade(color: Color, brightness: f64) -> Color {
+  match brightness {
+    1.0 => color,
+    b if b < 1.0 => color.interpolate(&Color::Black, 1.0 - b),
+    b => color.interpolate(&Color::White, (b - 1.0).min(1.0)),
+  }
+}
+
+/// The inverse of the standard normal cumulative distribution, using Acklam's rational
+/// approximation, which is accurate to about 1e-9.
+fn probit(p: f64) -> f64 {
+  const A: [f64; 6] = [
+    -3.969683028665376e1,
+    2.209460984245205e2,
+    -2.759285104469687e2,
+    1.38357751867269e2,
+    -3.066479806614716e1,
+    2.506628277459239,
+  ];
+  const B: [f64; 5] = [
+    -5.447609879822406e1,
+    1.615858368580409e2,
+    -1.556989798598866e2,
+    6.680131188771972e1,
+    -1.328068155288572e1,
+  ];
+  const C: [f64; 6] = [
+    -7.784894002430293e-3,
+    -3.223964580411365e-1,
+    -2.400758277161838,
+    -2.549732539343734,
+    4.374664141464968,
+    2.938163982698783,
+  ];
+  const D: [f64; 4] = [7.784695709041462e-3, 3.224671290700398e-1, 2.445134137142996, 3.754408661907416];
+  const P_LOW: f64 = 0.02425;
+
+  let p = p.clamp(1e-12, 1.0 - 1e-12);
+  let tail = |q: f64| (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5]) / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0);
+  if p < P_LOW {
+    tail((-2.0 * p.ln()).sqrt())
+  } else if p > 1.0 - P_LOW {
+    -tail((-2.0 * (1.0 - p).ln()).sqrt())
   } else {
-    let mut too_long = false;
-    for line in input.lines() {
-      if line.chars().count() > max_length {
-        too_long = true;
-        break;
-      }
+    let q = p - 0.5;
+    let r = q * q;
+    (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+  }
+}
+
+/// The RGB value of a color, using the common xterm values for the named colors.
+fn rgb(color: Color) -> Option<(u8, u8, u8)> {
+  match color {
+    Color::Black => Some((0, 0, 0)),
+    Color::Red => Some((205, 0, 0)),
+    Color::Green => Some((0, 205, 0)),
+    Color::Yellow => Some((205, 205, 0)),
+    Color::Blue => Some((0, 0, 238)),
+    Color::Magenta => Some((205, 0, 205)),
+    Color::Cyan => Some((0, 205, 205)),
+    Color::Gray => Some((229, 229, 229)),
+    Color::DarkGray => Some((127, 127, 127)),
+    Color::LightRed => Some((255, 0, 0)),
+    Color::LightGreen => Some((0, 255, 0)),
+    Color::LightYellow => Some((255, 255, 0)),
+    Color::LightBlue => Some((92, 92, 255)),
+    Color::LightMagenta => Some((255, 0, 255)),
+    Color::LightCyan => Some((0, 255, 255)),
+    Color::White => Some((255, 255, 255)),
+    Color::Rgb(r, g, b) => Some((r, g, b)),
+    _ => None,
+  }
+}
+
+/// Get the display width of a line of graphemes, in terminal columns.
+fn line_width(line: &[StyledGrapheme]) -> usize {
+  line.iter().map(|grapheme| grapheme.symbol.width()).sum()
+}
+
+fn is_whitespace(grapheme: &StyledGrapheme) -> bool {
+  grapheme.symbol.chars().all(char::is_whitespace)
+}
+
+/// Lay out lines of styled graphemes within `max_width` columns and `max_height` rows.
+///
+/// Lines that fit are kept as they are, including their indentation. Longer lines are
+/// wrapped at whitespace, and words wider than a whole line are broken with a hyphen.
+/// Widths are measured in terminal columns, so wide characters such as CJK or emoji
+/// count double. If there are more lines than rows, the last row ends in an ellipsis.
+fn wrap_lines(lines: Vec<Vec<StyledGrapheme>>, max_width: usize, max_height: usize) -> Vec<Vec<StyledGrapheme>> {
+  if max_width == 0 || max_height == 0 || lines.iter().all(|line| line.is_empty()) {
+    return vec![];
+  }
+
+  let mut rows = vec![];
+  for line in lines {
+    if line_width(&line) <= max_width {
+      rows.push(line);
+    } else {
+      wrap_line(line, max_width, &mut rows);
     }
-    if !too_long {
-      let mut result = Vec::new();
-      for line in input.lines() {
-        result.push(line.chars().collect());
-      }
-      //println!("{:?}", result);
-      result
+  }
+
+  if rows.len() > max_height {
+    rows.truncate(max_height);
+    let last = rows.last_mut().unwrap();
+    let style = last.last().map_or(Style::default(), |grapheme| grapheme.style);
+    // The ellipsis also replaces the hyphen of a broken word.
+    while line_width(last) + 1 > max_width || last.last().is_some_and(|grapheme| is_whitespace(grapheme) || grapheme.symbol == "-") {
+      last.pop();
+    }
+    last.push(StyledGrapheme::new("…", style));
+  }
+  rows
+}
+
+/// Wrap a single line that is too wide into rows of at most `max_width` columns.
+fn wrap_line<'a>(line: Vec<StyledGrapheme<'a>>, max_width: usize, rows: &mut Vec<Vec<StyledGrapheme<'a>>>) {
+  // Split the line into words, each with the whitespace preceding it.
+  let mut words: Vec<(Vec<StyledGrapheme>, Vec<StyledGrapheme>)> = vec![(vec![], vec![])];
+  for grapheme in line {
+    let (space, word) = words.last_mut().unwrap();
+    if !is_whitespace(&grapheme) {
+      word.push(grapheme);
+    } else if word.is_empty() {
+      space.push(grapheme);
     } else {
-      let cleaned_input = input.replace("\r\n", "").replace("\n", "");
-      let mut result = Vec::new();
-      let mut current_chunk = Vec::new();
-
-      for word in cleaned_input.split_whitespace() {
-        if current_chunk.len() + word.len() > max_length {
-          if !current_chunk.is_empty() {
-            result.push(current_chunk);
-            current_chunk = Vec::new();
-          }
-        }
+      words.push((vec![grapheme], vec![]));
+    }
+  }
 
-        if !current_chunk.is_empty() {
-          current_chunk.push(' ');
+  let mut row: Vec<StyledGrapheme> = vec![];
+  let mut first_row = true;
+  for (space, mut word) in words {
+    // Whitespace is kept as indentation on the first row, and dropped at row breaks.
+    let space_width = if row.is_empty() && !first_row { 0 } else { line_width(&space) };
+    if line_width(&row) + space_width + line_width(&word) <= max_width {
+      if space_width > 0 {
+        row.extend(space);
+      }
+      row.append(&mut word);
+      continue;
+    }
+    if word.is_empty() {
+      continue;
+    }
+    if !row.is_empty() {
+      rows.push(std::mem::take(&mut row));
+      first_row = false;
+    }
+
+    // Break words wider than a row, leaving a column for a hyphen where possible.
+    let piece_width = if max_width > 1 { max_width - 1 } else { max_width };
+    while line_width(&word) > max_width {
+      let mut taken = 0;
+      let mut split = 0;
+      for grapheme in &word {
+        taken += grapheme.symbol.width();
+        if taken > piece_width && split > 0 {
+          break;
         }
-        current_chunk.extend(word.chars());
+        split += 1;
+      }
+      let rest = word.split_off(split);
+      if max_width > 1 {
+        let style = word.last().map_or(Style::default(), |grapheme| grapheme.style);
+        word.push(StyledGrapheme::new("-", style));
       }
+      rows.push(std::mem::replace(&mut word, rest));
+      first_row = false;
+    }
+    row = word;
+  }
+  if !row.is_empty() {
+    rows.push(row);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use ratatui::{buffer::Cell, text::Line};
+
+  use super::*;
+
+  /// Wrap plain lines and return the rows as strings.
+  fn wrap(lines: &[&str], max_width: usize, max_height: usize) -> Vec<String> {
+    let lines: Vec<Line> = lines.iter().map(|line| Line::raw(*line)).collect();
+    let graphemes = lines.iter().map(|line| line.styled_graphemes(Style::default()).collect()).collect();
+    wrap_lines(graphemes, max_width, max_height)
+      .iter()
+      .map(|row| row.iter().map(|grapheme| grapheme.symbol).collect())
+      .collect()
+  }
 
-      if !current_chunk.is_empty() {
-        result.push(current_chunk);
+  #[test]
+  fn time_to_reach_inverts_the_integral() {
+    let seconds = Duration::from_secs;
+    for easing in [Easing::Linear, Easing::Step, Easing::EaseIn, Easing::EaseOut, Easing::EaseInOut] {
+      for repeat in [false, true] {
+        let speed = Animated::new(2.0)
+          .then(seconds(4), 10.0, easing)
+          .then(seconds(3), 10.0, Easing::Linear)
+          .then(seconds(5), 0.5, easing)
+          .with_repeat(repeat);
+        for elapsed in [0.0, 0.5, 3.9, 4.0, 6.2, 11.0, 12.0, 19.5, 40.0] {
+          let integral = speed.integrate(Duration::from_secs_f64(elapsed), |speed| *speed);
+          let time = speed.time_to_reach(integral, |speed| *speed);
+          assert!((time - elapsed).abs() < 1e-6, "{easing:?} repeat={repeat}: {time} != {elapsed}");
+        }
       }
+    }
+  }
+
+  #[test]
+  fn wrap_lines_keeps_lines_that_fit() {
+    assert_eq!(wrap(&["  indented", "", "ok"], 10, 5), ["  indented", "", "ok"]);
+    assert_eq!(wrap(&["anything"], 0, 5), Vec::<String>::new());
+    assert_eq!(wrap(&["anything"], 5, 0), Vec::<String>::new());
+  }
+
+  #[test]
+  fn wrap_lines_breaks_at_whitespace() {
+    assert_eq!(wrap(&["  the quick brown fox"], 11, 5), ["  the quick", "brown fox"]);
+  }
+
+  #[test]
+  fn wrap_lines_breaks_words_wider_than_a_row_with_a_hyphen() {
+    assert_eq!(wrap(&["a supercalifragilistic word"], 8, 10), ["a", "superca-", "lifragi-", "listic", "word"]);
+    assert_eq!(wrap(&["abc"], 1, 5), ["a", "b", "c"]);
+  }
+
+  #[test]
+  fn wrap_lines_measures_wide_characters_in_columns() {
+    // Each CJK character is two columns wide.
+    assert_eq!(wrap(&["你好 世界 再见"], 9, 5), ["你好 世界", "再见"]);
+    assert_eq!(wrap(&["日本語のテキスト"], 6, 5), ["日本-", "語の-", "テキ-", "スト"]);
+  }
+
+  #[test]
+  fn wrap_lines_never_splits_emoji_sequences() {
+    // A woman technologist is a single grapheme joined with a zero width joiner.
+    assert_eq!(wrap(&["hi 👩‍💻👩‍💻 ok"], 4, 5), ["hi", "👩‍💻👩‍💻", "ok"]);
+    assert_eq!(wrap(&["👩‍💻👩‍💻"], 3, 5), ["👩‍💻-", "👩‍💻"]);
+  }
+
+  #[test]
+  fn wrap_lines_truncates_with_an_ellipsis() {
+    assert_eq!(wrap(&["one", "two c", "three"], 5, 2), ["one", "two…"]);
+    assert_eq!(wrap(&["first line", "second line", "third"], 11, 2), ["first line", "second lin…"]);
+    assert_eq!(wrap(&["一二三四五六"], 6, 1), ["一二…"]);
+  }
 
-      result
+  #[test]
+  fn draw_stays_inside_an_offset_area() {
+    let area = Rect::new(5, 3, 20, 10);
+    for resolution in [Resolution::Cell, Resolution::Braille] {
+      let mut buf = Buffer::filled(Rect::new(0, 0, 40, 20), Cell::new("#"));
+      Rain::new_matrix(Duration::from_secs(5))
+        .with_resolution(resolution)
+        .with_message("Hi")
+        .with_message_backdrop(MessageBackdrop::Border {
+          border_type: BorderType::Plain,
+        })
+        .with_verbose(true)
+        .render(area, &mut buf);
+      for position in buf.area.positions().filter(|position| !area.contains(*position)) {
+        assert_eq!(buf[position].symbol(), "#", "drew outside the area at {position:?}");
+      }
+      let rows: Vec<String> = (area.top()..area.bottom())
+        .map(|y| (area.left()..area.right()).map(|x| buf[(x, y)].symbol()).collect())
+        .collect();
+      assert!(rows.iter().any(|row| row.contains("Hi")), "no message in {rows:#?}");
     }
   }
 }
@@ -1,111 +1,176 @@
 mod args;
+mod markup;
+mod mask;
+mod message;
+mod scene;
+mod template;
 mod utils;
-use std::time::Duration;
+use std::{
+  io::IsTerminal,
+  sync::{Arc, Mutex},
+  time::Duration,
+};
 
-use clap::Parser;
 use std::error::Error;
-use tui_rain_cli::{CharacterSet, Rain, RainDensity, RainSpeed};
+use tui_rain_cli::{Capabilities, ColumnLock, EffectRegistry, Mask, Rain, RainDensity, RainSpeed};
+
+/// Inputs that feed the rain while it is running, besides the parsed arguments.
+struct Sources {
+  stdin_glyphs: Option<Arc<Mutex<utils::GlyphRing>>>,
+  live_message: Option<message::SharedMessage>,
+  playlist: Vec<message::PlaylistItem>,
+  template_context: template::TemplateContext,
+  mask: Option<Mask>,
+  scene: Option<scene::Scene>,
+  effects: EffectRegistry,
+  capabilities: Capabilities,
+}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-  let mut args = args::Args::parse();
-  args.sanitize();
-  let rain_density = RainDensity::Relative {
-    sparseness: args.density.unwrap(),
+  let effects = args::effects();
+  let mut args = args::Args::parse_with(&effects);
+  let scene = match &args.scene {
+    Some(path) => Some(scene::load_scene(path, &args, &effects)?),
+    None => None,
   };
-  let rain_speed = RainSpeed::Absolute { speed: args.speed.unwrap() };
-  let message_speed = RainSpeed::Absolute {
-    speed: args.message_speed.unwrap(),
+  args.sanitize(&effects);
+
+  if args.stdin && std::io::stdin().is_terminal() {
+    return Err("--stdin expects piped input, e.g. `tail -f app.log | tui-rain-cli -t data --stdin`".into());
+  }
+
+  let fallback = args.message_fallback.clone().unwrap_or_default();
+  let sources = Sources {
+    stdin_glyphs: args.stdin.then(utils::spawn_stdin_reader),
+    live_message: match (&args.message_file, &args.message_cmd) {
+      (Some(path), _) => Some(message::watch_file(path.clone(), fallback)),
+      (None, Some(command)) => Some(message::watch_command(command.clone(), args.message_interval, fallback)),
+      (None, None) => None,
+    },
+    playlist: match &args.message_playlist {
+      Some(path) => message::load_playlist(path, args.message_duration)?,
+      None if args.message.len() > 1 => args
+        .message
+        .iter()
+        .map(|text| message::PlaylistItem::new(text.clone(), args.message_duration))
+        .collect(),
+      None => vec![],
+    },
+    template_context: template::TemplateContext::new(),
+    mask: match &args.mask {
+      Some(path) => Some(mask::load(path, args.mask_color.or(args.head_color).unwrap())?),
+      None => None,
+    },
+    scene,
+    effects,
+    capabilities: args.capabilities(|name| std::env::var(name).ok()),
   };
 
-  let rain_speed_variance = args.variance_speed.unwrap_or(0.5);
-  let tail_lifespan = Duration::from_millis(args.lifespan_tail.unwrap_or(250));
-  match args.rain_type {
-    args::RainType::Rain => {
-      utils::render_rain(Box::new(move |elapsed| {
-        Rain::new_rain(elapsed)
-          .with_rain_density(rain_density)
-          .with_rain_speed(rain_speed)
-          .with_rain_speed_variance(rain_speed_variance)
-          .with_tail_lifespan(tail_lifespan)
-          .with_color(args.color.unwrap())
-          .with_head_color(args.head_color.unwrap())
-          .with_bold_dim_effect(args.effect_dim.unwrap())
-          .with_message(args.message.as_ref().unwrap().as_str())
-          .with_message_color(args.message_color.unwrap())
-          .with_message_speed(message_speed)
-          .with_verbose(args.verbose)
-      }))
-      .await
-    }
-    args::RainType::Matrix => {
-      utils::render_rain(Box::new(move |elapsed| {
-        Rain::new_matrix(elapsed)
-          .with_rain_density(rain_density)
-          .with_rain_speed(rain_speed)
-          .with_rain_speed_variance(rain_speed_variance)
-          .with_tail_lifespan(tail_lifespan)
-          .with_color(args.color.unwrap())
-          .with_head_color(args.head_color.unwrap())
-          .with_bold_dim_effect(args.effect_dim.unwrap())
-          .with_message(args.message.as_ref().unwrap_or(&String::from("")).as_str())
-          .with_message_color(args.message_color.unwrap())
-          .with_message_speed(message_speed)
-          .with_verbose(args.verbose)
-      }))
-      .await
-    }
-    args::RainType::Snow => {
-      utils::render_rain(Box::new(move |elapsed| {
-        Rain::new_snow(elapsed)
-          .with_rain_density(rain_density)
-          .with_rain_speed(rain_speed)
-          .with_rain_speed_variance(rain_speed_variance)
-          .with_tail_lifespan(tail_lifespan)
-          .with_color(args.color.unwrap())
-          .with_head_color(args.head_color.unwrap())
-          .with_bold_dim_effect(args.effect_dim.unwrap())
-          .with_message(args.message.as_ref().unwrap_or(&String::from("")).as_str())
-          .with_message_color(args.message_color.unwrap())
-          .with_message_speed(message_speed)
-          .with_verbose(args.verbose)
-      }))
-      .await
-    }
-    args::RainType::Data => {
-      utils::render_rain(Box::new(move |elapsed| {
-        Rain::new_matrix(elapsed)
-          .with_rain_density(rain_density)
-          .with_rain_speed(rain_speed)
-          .with_rain_speed_variance(rain_speed_variance)
-          .with_tail_lifespan(tail_lifespan)
-          .with_color(args.color.unwrap())
-          .with_head_color(args.head_color.unwrap())
-          .with_bold_dim_effect(args.effect_dim.unwrap())
-          .with_character_set(CharacterSet::Explicit { options: vec!['0', '1'] })
-          .with_message(args.message.as_ref().unwrap_or(&String::from("")).as_str())
-          .with_message_color(args.message_color.unwrap())
-          .with_message_speed(message_speed)
-          .with_verbose(args.verbose)
-      }))
-      .await
-    }
-    args::RainType::Emoji => {
-      utils::render_rain(Box::new(move |elapsed| {
-        Rain::new_emoji_soup(elapsed)
-          .with_rain_density(rain_density)
-          .with_rain_speed(rain_speed)
-          .with_rain_speed_variance(rain_speed_variance)
-          .with_tail_lifespan(tail_lifespan)
-          .with_color(args.color.unwrap())
-          .with_head_color(args.head_color.unwrap())
-          .with_bold_dim_effect(args.effect_dim.unwrap())
-          .with_message(args.message.as_ref().unwrap_or(&String::from("")).as_str())
-          .with_message_color(args.message_color.unwrap())
-          .with_message_speed(message_speed)
-          .with_verbose(args.verbose)
-      }))
-      .await
-    }
+  let graphics = args.graphics.protocol();
+  let capabilities = sources.capabilities;
+  utils::render_rain(
+    Box::new(move |elapsed, fps| match &sources.scene {
+      Some(scene) => {
+        let frame = scene.frame(elapsed);
+        let message_start = frame.drop_elapsed.saturating_sub(frame.step_elapsed);
+        build_layers(&frame.args, &sources, frame.drop_elapsed, elapsed, fps, Some(message_start))
+      }
+      None => build_layers(&args, &sources, elapsed, elapsed, fps, None),
+    }),
+    graphics,
+    capabilities,
+  )
+  .await
+}
+
+/// Build the layers of the current frame from the sanitized arguments: the effect, and
+/// the rain carrying the message on top.
+///
+/// Rain presets are a single layer, so the message can interact with their drops. Other
+/// effects are drawn below rain without any drops.
+///
+/// The layers are built at `elapsed`, while templates show the wall-clock time since the
+/// start, `clock`. The two only differ in scenes, where drops follow the distance fallen.
+fn build_layers(args: &args::Args, sources: &Sources, elapsed: Duration, clock: Duration, fps: f64, message_start: Option<Duration>) -> utils::Layers {
+  let config = args.effect_config();
+  let (effect, rain) = match sources.effects.build_rain(&args.rain_type, elapsed, &config) {
+    Some(rain) => (None, rain),
+    None => (
+      sources.effects.build(&args.rain_type, &config),
+      Rain::new_rain(elapsed)
+        .with_config(&config)
+        .with_rain_density(RainDensity::Absolute { num_drops: 0 }),
+    ),
+  };
+  let mut rain = build_rain(rain, args, sources, elapsed, clock, fps);
+  if let Some(message_start) = message_start {
+    rain = rain.with_message_start(message_start);
+  }
+  let mut layers: utils::Layers = effect.into_iter().map(|effect| (elapsed, effect)).collect();
+  layers.push((elapsed, Box::new(rain)));
+  layers
+}
+
+/// Configure the rain for the current frame from the sanitized arguments.
+fn build_rain(mut rain: Rain, args: &args::Args, sources: &Sources, elapsed: Duration, clock: Duration, fps: f64) -> Rain {
+  let render_message = |message: &str| markup::parse(&sources.template_context.render(message, clock, fps));
+  if let Some(live_message) = &sources.live_message {
+    let live_message = live_message.lock().unwrap();
+    rain = rain
+      .with_message_text(render_message(live_message.text()))
+      .with_message_start(elapsed.saturating_sub(live_message.age()));
+  } else {
+    rain = rain.with_message_text(render_message(args.message.first().map_or("", String::as_str)));
+  }
+  if !sources.playlist.is_empty() {
+    rain = rain.with_message_playlist(sources.playlist.iter().map(|item| item.entry(render_message)).collect());
+  }
+  if let Some(character_set) = sources.stdin_glyphs.as_ref().and_then(|glyphs| glyphs.lock().unwrap().character_set()) {
+    rain = rain.with_character_set(character_set);
+  }
+  if let Some(mask) = &sources.mask {
+    rain = rain.with_mask(mask.clone());
+  }
+  if let Some(direction) = args.direction {
+    rain = rain.with_direction(direction);
+  }
+  if let Some(gravity) = args.gravity {
+    rain = rain.with_gravity(gravity);
+  }
+  if let Some(spacing) = args.columns {
+    rain = rain.with_column_lock(ColumnLock {
+      spacing,
+      ..ColumnLock::default()
+    });
+  }
+  if let Some(speed_distribution) = args.speed_distribution {
+    rain = rain.with_speed_distribution(speed_distribution);
+  }
+  if let Some(tail_variation) = args.tail_variation {
+    rain = rain.with_tail_variation(tail_variation);
+  }
+  if let Some(brightness_variation) = args.brightness_variation {
+    rain = rain.with_brightness_variation(brightness_variation);
+  }
+  if let Some(head_color_variation) = args.head_color_variation {
+    rain = rain.with_head_color_variation(head_color_variation);
   }
+  rain
+    .with_message_speed(RainSpeed::Absolute {
+      speed: args.message_speed.unwrap(),
+    })
+    .with_message_effect(args.message_effect.into())
+    .with_decode_timeout(args.decode_timeout)
+    .with_message_position(args.message_position)
+    .with_message_alignment(args.message_align)
+    .with_message_padding(args.message_padding)
+    .with_message_edge(args.message_edge)
+    .with_message_backdrop(args.message_backdrop)
+    .with_message_backdrop_margin(args.backdrop_margin)
+    .with_backdrop_drops(args.backdrop_drops)
+    .with_mask_mode(args.mask_mode)
+    .with_depth_correlation(args.depth)
+    .with_capabilities(sources.capabilities)
+    .with_verbose(args.verbose)
 }
@@ -0,0 +1,161 @@
+use ratatui::{
+  style::{Color, Modifier, Style},
+  text::{Line, Span, Text},
+};
+
+/// Parse a message with inline style tags into rich text.
+///
+/// Tags are written in square brackets and apply until the matching closing tag, e.g.
+/// `Happy [b]2027[/b] [red]team[/]`. `[/]` closes the most recently opened tag.
+///
+/// Supported tags are the modifiers `b`/`bold`, `d`/`dim`, `i`/`italic`,
+/// `u`/`underline`, `s`/`strike`, `r`/`reverse` and `blink`, and any color accepted
+/// by `--color` (including `#rrggbb`), optionally followed by `on <color>` for the
+/// background (or just `on <color>`). `[[` produces a literal `[`, and unknown tags
+/// are kept as text.
+pub fn parse(markup: &str) -> Text<'static> {
+  let mut lines = vec![];
+  let mut spans = vec![];
+  let mut text = String::new();
+  let mut stack: Vec<(String, Style)> = vec![];
+
+  let current_style = |stack: &[(String, Style)]| stack.iter().fold(Style::default(), |style, (_, tag)| style.patch(*tag));
+
+  let mut rest = markup;
+  while let Some(start) = rest.find(['[', '\n']) {
+    text.push_str(&rest[..start]);
+    rest = &rest[start..];
+
+    if let Some(after) = rest.strip_prefix('\n') {
+      flush(&mut spans, &mut text, current_style(&stack));
+      lines.push(Line::from(std::mem::take(&mut spans)));
+      rest = after;
+      continue;
+    }
+    if let Some(after) = rest.strip_prefix("[[") {
+      text.push('[');
+      rest = after;
+      continue;
+    }
+
+    let tag = rest[1..].find(']').map(|end| &rest[1..end + 1]);
+    let applied = match tag {
+      Some(tag) => match tag.strip_prefix('/') {
+        Some(name) => {
+          let position = stack.iter().rposition(|(open, _)| name.is_empty() || open == name);
+          position.map(|position| {
+            flush(&mut spans, &mut text, current_style(&stack));
+            stack.remove(position);
+          })
+        }
+        None => tag_style(tag).map(|style| {
+          flush(&mut spans, &mut text, current_style(&stack));
+          stack.push((tag.to_string(), style));
+        }),
+      },
+      None => None,
+    };
+    match (applied, tag) {
+      (Some(()), Some(tag)) => rest = &rest[tag.len() + 2..],
+      _ => {
+        text.push('[');
+        rest = &rest[1..];
+      }
+    }
+  }
+  text.push_str(rest);
+  flush(&mut spans, &mut text, current_style(&stack));
+  lines.push(Line::from(spans));
+  Text::from(lines)
+}
+
+/// Move the accumulated text into a span with the given style.
+fn flush(spans: &mut Vec<Span<'static>>, text: &mut String, style: Style) {
+  if !text.is_empty() {
+    spans.push(Span::styled(std::mem::take(text), style));
+  }
+}
+
+/// Get the style for an opening tag, or `None` if it isn't a known tag.
+fn tag_style(tag: &str) -> Option<Style> {
+  let modifier = match tag {
+    "b" | "bold" => Some(Modifier::BOLD),
+    "d" | "dim" => Some(Modifier::DIM),
+    "i" | "italic" => Some(Modifier::ITALIC),
+    "u" | "underline" => Some(Modifier::UNDERLINED),
+    "s" | "strike" => Some(Modifier::CROSSED_OUT),
+    "r" | "reverse" => Some(Modifier::REVERSED),
+    "blink" => Some(Modifier::SLOW_BLINK),
+    _ => None,
+  };
+  if let Some(modifier) = modifier {
+    return Some(Style::default().add_modifier(modifier));
+  }
+
+  let (fg, bg) = match (tag.strip_prefix("on "), tag.split_once(" on ")) {
+    (Some(bg), _) => ("", Some(bg.trim())),
+    (None, Some((fg, bg))) => (fg.trim(), Some(bg.trim())),
+    (None, None) => (tag, None),
+  };
+  let mut style = Style::default();
+  if !fg.is_empty() {
+    style = style.fg(fg.parse::<Color>().ok()?);
+  }
+  if let Some(bg) = bg {
+    style = style.bg(bg.parse::<Color>().ok()?);
+  }
+  Some(style)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_styles_tagged_spans() {
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    assert_eq!(
+      parse("Happy [b]2027[/b] [red on #000080]team[/]!"),
+      Text::from(Line::from(vec![
+        Span::raw("Happy "),
+        Span::styled("2027", bold),
+        Span::raw(" "),
+        Span::styled("team", Style::default().fg(Color::Red).bg(Color::Rgb(0, 0, 0x80))),
+        Span::raw("!"),
+      ]))
+    );
+  }
+
+  #[test]
+  fn parse_nests_tags_and_closes_them_by_name() {
+    let yellow = Style::default().fg(Color::Yellow);
+    assert_eq!(
+      parse("[yellow]a[i]b[/yellow]c[/i]d"),
+      Text::from(Line::from(vec![
+        Span::styled("a", yellow),
+        Span::styled("b", yellow.add_modifier(Modifier::ITALIC)),
+        Span::styled("c", Style::default().add_modifier(Modifier::ITALIC)),
+        Span::raw("d"),
+      ]))
+    );
+  }
+
+  #[test]
+  fn parse_keeps_styles_across_lines() {
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    assert_eq!(
+      parse("[dim]one\ntwo[/dim]\nthree"),
+      Text::from(vec![
+        Line::from(Span::styled("one", dim)),
+        Line::from(Span::styled("two", dim)),
+        Line::from(Span::raw("three")),
+      ])
+    );
+  }
+
+  #[test]
+  fn parse_keeps_unknown_tags_and_escapes_as_text() {
+    assert_eq!(parse("[[b] [sparkle]x[/u] [b"), Text::from(Line::from(Span::raw("[b] [sparkle]x[/u] [b"))));
+    assert_eq!(parse("[on]x"), Text::from(Line::from(Span::raw("[on]x"))));
+  }
+}
@@ -0,0 +1,168 @@
+use std::{fs, path::Path};
+
+use ratatui::style::Color;
+use tui_rain_cli::Mask;
+
+/// Pixels without an alpha channel darker than this in every channel are left unset.
+const BRIGHTNESS_THRESHOLD: u8 = 32;
+
+/// Load a mask from a PNG, a PPM/PGM image or an ASCII-art text file, by extension.
+///
+/// Images keep their pixel colors. Pixels are set where they're opaque, or, for images
+/// without an alpha channel, where they aren't black. ASCII art is set to `color`
+/// wherever it isn't a space.
+pub fn load(path: &Path, color: Color) -> Result<Mask, String> {
+  let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or_default();
+  let mask = match extension.to_lowercase().as_str() {
+    "png" => load_png(path),
+    "ppm" | "pgm" | "pnm" => fs::read(path).map_err(|e| e.to_string()).and_then(|data| parse_pnm(&data)),
+    _ => fs::read_to_string(path).map(|art| Mask::from_ascii(&art, color)).map_err(|e| e.to_string()),
+  };
+  mask.map_err(|e| format!("failed to load mask {}: {e}", path.display()))
+}
+
+/// Decode a PNG image into a mask.
+fn load_png(path: &Path) -> Result<Mask, String> {
+  let file = fs::File::open(path).map_err(|e| e.to_string())?;
+  let mut decoder = png::Decoder::new(file);
+  decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+  let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+  let mut data = vec![0; reader.output_buffer_size()];
+  let frame = reader.next_frame(&mut data).map_err(|e| e.to_string())?;
+  let pixels = data[..frame.buffer_size()]
+    .chunks_exact(frame.color_type.samples())
+    .map(|pixel| match *pixel {
+      [gray] => pixel_color(gray, gray, gray, None),
+      [gray, alpha] => pixel_color(gray, gray, gray, Some(alpha)),
+      [r, g, b] => pixel_color(r, g, b, None),
+      [r, g, b, alpha] => pixel_color(r, g, b, Some(alpha)),
+      _ => None,
+    })
+    .collect();
+  Ok(Mask::from_pixels(frame.width as usize, frame.height as usize, pixels))
+}
+
+/// Parse a PPM or PGM image, in either the plain (`P2`, `P3`) or binary (`P5`, `P6`) format.
+fn parse_pnm(data: &[u8]) -> Result<Mask, String> {
+  let mut position = 0;
+  let mut header = vec![];
+  while header.len() < 4 {
+    header.push(next_token(data, &mut position).ok_or("truncated header")?);
+  }
+  let number = |token: &str| token.parse::<usize>().map_err(|_| format!("invalid header value `{token}`"));
+  let (width, height, max) = (number(&header[1])?, number(&header[2])?, number(&header[3])?);
+  if max == 0 || max > u16::MAX as usize {
+    return Err(format!("invalid maximum value `{max}`"));
+  }
+  let (channels, binary) = match header[0].as_str() {
+    "P2" => (1, false),
+    "P3" => (3, false),
+    "P5" => (1, true),
+    "P6" => (3, true),
+    magic => return Err(format!("unsupported format `{magic}`, expected P2, P3, P5 or P6")),
+  };
+
+  let count = width
+    .checked_mul(height)
+    .and_then(|count| count.checked_mul(channels))
+    .ok_or("image too large")?;
+  let samples: Vec<usize> = if binary {
+    // A single whitespace character separates the header from the binary data.
+    let bytes = if max > 255 { 2 } else { 1 };
+    let start = position + 1;
+    let raw = data
+      .get(start..start + count.checked_mul(bytes).ok_or("image too large")?)
+      .ok_or("truncated pixel data")?;
+    raw
+      .chunks_exact(bytes)
+      .map(|sample| sample.iter().fold(0, |value, byte| value << 8 | *byte as usize))
+      .collect()
+  } else {
+    (0..count)
+      .map(|_| {
+        next_token(data, &mut position)
+          .ok_or("truncated pixel data".to_string())
+          .and_then(|token| number(&token))
+      })
+      .collect::<Result<_, _>>()?
+  };
+
+  let scale = |sample: usize| (sample.min(max) * 255 / max) as u8;
+  let pixels = samples
+    .chunks_exact(channels)
+    .map(|pixel| match *pixel {
+      [gray] => pixel_color(scale(gray), scale(gray), scale(gray), None),
+      [r, g, b] => pixel_color(scale(r), scale(g), scale(b), None),
+      _ => None,
+    })
+    .collect();
+  Ok(Mask::from_pixels(width, height, pixels))
+}
+
+/// Read the next whitespace separated token of a PNM header, skipping `#` comments.
+fn next_token(data: &[u8], position: &mut usize) -> Option<String> {
+  loop {
+    match data.get(*position)? {
+      byte if byte.is_ascii_whitespace() => *position += 1,
+      b'#' => {
+        while *data.get(*position)? != b'\n' {
+          *position += 1;
+        }
+      }
+      _ => break,
+    }
+  }
+  let start = *position;
+  while data.get(*position).is_some_and(|byte| !byte.is_ascii_whitespace()) {
+    *position += 1;
+  }
+  Some(String::from_utf8_lossy(&data[start..*position]).into_owned())
+}
+
+/// Decide whether a pixel is set, and its color if so.
+fn pixel_color(r: u8, g: u8, b: u8, alpha: Option<u8>) -> Option<Color> {
+  let set = match alpha {
+    Some(alpha) => alpha >= 128,
+    None => r.max(g).max(b) >= BRIGHTNESS_THRESHOLD,
+  };
+  set.then_some(Color::Rgb(r, g, b))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_pnm_reads_plain_images() {
+    let mask = parse_pnm(b"P3\n# a comment\n2 1\n255\n255 0 0  0 0 0\n").unwrap();
+    assert_eq!(mask, Mask::from_pixels(2, 1, vec![Some(Color::Rgb(255, 0, 0)), None]));
+  }
+
+  #[test]
+  fn parse_pnm_scales_samples_to_the_maximum_value() {
+    let mask = parse_pnm(b"P2 2 2 15 15 0 8 1").unwrap();
+    let gray = |value| Some(Color::Rgb(value, value, value));
+    // A sample of 1 scales to 17, which is too dark to be set.
+    assert_eq!(mask, Mask::from_pixels(2, 2, vec![gray(255), None, gray(136), None]));
+  }
+
+  #[test]
+  fn parse_pnm_reads_binary_images() {
+    let mask = parse_pnm(b"P5\n2 1\n255\n\xc8\x0a").unwrap();
+    assert_eq!(mask, Mask::from_pixels(2, 1, vec![Some(Color::Rgb(200, 200, 200)), None]));
+
+    // Samples take two bytes, most significant first, when the maximum is above 255.
+    let mask = parse_pnm(b"P6 1 1 65535\n\xff\xff\x00\x00\x80\x00").unwrap();
+    assert_eq!(mask, Mask::from_pixels(1, 1, vec![Some(Color::Rgb(255, 0, 127))]));
+  }
+
+  #[test]
+  fn parse_pnm_rejects_invalid_images() {
+    assert_eq!(parse_pnm(b"P3 2").unwrap_err(), "truncated header");
+    assert_eq!(parse_pnm(b"P4 1 1 1 0").unwrap_err(), "unsupported format `P4`, expected P2, P3, P5 or P6");
+    assert_eq!(parse_pnm(b"P2 x 1 255 0").unwrap_err(), "invalid header value `x`");
+    assert_eq!(parse_pnm(b"P2 1 1 0 0").unwrap_err(), "invalid maximum value `0`");
+    assert_eq!(parse_pnm(b"P3 1 1 255 0 0").unwrap_err(), "truncated pixel data");
+    assert_eq!(parse_pnm(b"P6 2 1 255\n\x00\x00\x00").unwrap_err(), "truncated pixel data");
+  }
+}
@@ -0,0 +1,262 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+  process::Command,
+  sync::{Arc, Mutex},
+  thread,
+  time::{Duration, Instant, SystemTime},
+};
+
+use clap::ValueEnum;
+use ratatui::{style::Color, text::Text};
+use tui_rain_cli::{PlaylistEntry, RainSpeed};
+
+use crate::args;
+
+/// How often a message file is checked for modifications.
+const FILE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A message that is refreshed in the background while the rain is rendering.
+#[derive(Debug)]
+pub struct LiveMessage {
+  text: String,
+  changed_at: Instant,
+}
+
+impl LiveMessage {
+  fn new(text: String) -> LiveMessage {
+    LiveMessage {
+      text,
+      changed_at: Instant::now(),
+    }
+  }
+
+  /// Replace the text, restarting the drop-in animation only if it actually changed.
+  fn set(&mut self, text: String) {
+    if text != self.text {
+      self.text = text;
+      self.changed_at = Instant::now();
+    }
+  }
+
+  /// The current message text.
+  pub fn text(&self) -> &str {
+    &self.text
+  }
+
+  /// How long ago the text last changed.
+  pub fn age(&self) -> Duration {
+    self.changed_at.elapsed()
+  }
+}
+
+pub type SharedMessage = Arc<Mutex<LiveMessage>>;
+
+/// Watch a file and reload the message whenever its modification time changes.
+///
+/// If the file can't be read, the fallback is displayed until it can.
+pub fn watch_file(path: PathBuf, fallback: String) -> SharedMessage {
+  let message = Arc::new(Mutex::new(LiveMessage::new(fallback.clone())));
+  let writer = message.clone();
+  thread::spawn(move || {
+    let mut last_modified: Option<SystemTime> = None;
+    loop {
+      match fs::metadata(&path).and_then(|metadata| metadata.modified()) {
+        Ok(modified) if last_modified == Some(modified) => {}
+        Ok(modified) => {
+          last_modified = Some(modified);
+          let text = fs::read_to_string(&path).map(|text| text.trim_end().to_string());
+          writer.lock().unwrap().set(text.unwrap_or_else(|_| fallback.clone()));
+        }
+        Err(_) => {
+          last_modified = None;
+          writer.lock().unwrap().set(fallback.clone());
+        }
+      }
+      thread::sleep(FILE_POLL_INTERVAL);
+    }
+  });
+  message
+}
+
+/// Run a shell command every `interval` and display its standard output.
+///
+/// If the command can't be spawned or exits unsuccessfully, the fallback is displayed
+/// until the next successful run.
+pub fn watch_command(command: String, interval: Duration, fallback: String) -> SharedMessage {
+  let message = Arc::new(Mutex::new(LiveMessage::new(fallback.clone())));
+  let writer = message.clone();
+  thread::spawn(move || loop {
+    let text = match shell(&command).output() {
+      Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).trim_end().to_string(),
+      _ => fallback.clone(),
+    };
+    writer.lock().unwrap().set(text);
+    thread::sleep(interval);
+  });
+  message
+}
+
+/// Build a command that runs `command` through the platform shell.
+fn shell(command: &str) -> Command {
+  let mut shell = if cfg!(windows) { Command::new("cmd") } else { Command::new("sh") };
+  shell.arg(if cfg!(windows) { "/C" } else { "-c" }).arg(command);
+  shell
+}
+
+/// A message in a playlist, before its placeholders are expanded.
+#[derive(Clone, Debug)]
+pub struct PlaylistItem {
+  text: String,
+  duration: Duration,
+  color: Option<Color>,
+  speed: Option<f64>,
+  effect: Option<args::MessageEffect>,
+}
+
+impl PlaylistItem {
+  /// Construct an item styled like the rest of the messages.
+  pub fn new(text: String, duration: Duration) -> PlaylistItem {
+    PlaylistItem {
+      text,
+      duration,
+      color: None,
+      speed: None,
+      effect: None,
+    }
+  }
+
+  /// Build the playlist entry for this item, turning its text into rich text with `render`.
+  pub fn entry(&self, render: impl Fn(&str) -> Text<'static>) -> PlaylistEntry {
+    let mut entry = PlaylistEntry::from_text(render(&self.text), self.duration);
+    if let Some(color) = self.color {
+      entry = entry.with_color(color);
+    }
+    if let Some(speed) = self.speed {
+      entry = entry.with_speed(RainSpeed::Absolute { speed });
+    }
+    if let Some(effect) = self.effect {
+      entry = entry.with_effect(effect.into());
+    }
+    entry
+  }
+}
+
+/// Load a playlist file.
+///
+/// Messages are separated by header lines starting with `---`, followed by an optional
+/// duration and `key=value` settings for `duration`, `color`, `speed` and `effect`:
+///
+/// ```text
+/// --- 10s color=yellow speed=3
+/// Happy New Year!
+/// --- 5s effect=blink
+/// See you in 2027
+/// ```
+///
+/// Text before the first header is shown for `default_duration`. A header without text
+/// below it leaves a pause without a message.
+pub fn load_playlist(path: &Path, default_duration: Duration) -> Result<Vec<PlaylistItem>, String> {
+  let content = fs::read_to_string(path).map_err(|e| format!("failed to read playlist {}: {e}", path.display()))?;
+  let mut items = vec![PlaylistItem::new(String::new(), default_duration)];
+  for (number, line) in content.lines().enumerate() {
+    match line.strip_prefix("---") {
+      Some(header) => {
+        let item = parse_header(header, default_duration).map_err(|e| format!("{}:{}: {e}", path.display(), number + 1))?;
+        items.push(item);
+      }
+      None => {
+        let text = &mut items.last_mut().unwrap().text;
+        if !text.is_empty() {
+          text.push('\n');
+        }
+        text.push_str(line);
+      }
+    }
+  }
+  for item in &mut items {
+    item.text.truncate(item.text.trim_end().len());
+  }
+  if items[0].text.is_empty() {
+    items.remove(0);
+  }
+  if items.is_empty() {
+    return Err(format!("playlist {} contains no messages", path.display()));
+  }
+  Ok(items)
+}
+
+/// Parse the settings of a playlist header line, after the leading `---`.
+fn parse_header(header: &str, default_duration: Duration) -> Result<PlaylistItem, String> {
+  let mut item = PlaylistItem::new(String::new(), default_duration);
+  for token in header.split_whitespace() {
+    let (key, value) = token.split_once('=').unwrap_or(("duration", token));
+    match key {
+      "duration" => item.duration = args::parse_duration(value)?,
+      "color" => item.color = Some(value.parse().map_err(|_| format!("invalid color `{value}`"))?),
+      "speed" => item.speed = Some(value.parse().map_err(|_| format!("invalid speed `{value}`"))?),
+      "effect" => item.effect = Some(args::MessageEffect::from_str(value, true)?),
+      key => return Err(format!("unknown setting `{key}`, expected one of duration, color, speed, effect")),
+    }
+  }
+  Ok(item)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Write a playlist to a temporary file and load it.
+  fn load(name: &str, content: &str) -> Result<Vec<PlaylistItem>, String> {
+    let path = std::env::temp_dir().join(format!("tui-rain-playlist-{}-{name}", std::process::id()));
+    fs::write(&path, content).unwrap();
+    let items = load_playlist(&path, Duration::from_secs(10));
+    fs::remove_file(&path).unwrap();
+    items
+  }
+
+  #[test]
+  fn load_playlist_splits_messages_at_headers() {
+    let items = load(
+      "split",
+      "Welcome\n--- 5s color=yellow speed=3 effect=blink\nHappy\nNew Year!\n\n---\n--- duration=1m\nBye\n",
+    )
+    .unwrap();
+    let summary: Vec<_> = items.iter().map(|item| (item.text.as_str(), item.duration)).collect();
+    assert_eq!(
+      summary,
+      [
+        ("Welcome", Duration::from_secs(10)),
+        ("Happy\nNew Year!", Duration::from_secs(5)),
+        ("", Duration::from_secs(10)),
+        ("Bye", Duration::from_secs(60)),
+      ]
+    );
+    assert_eq!(items[1].color, Some(Color::Yellow));
+    assert_eq!(items[1].speed, Some(3.0));
+    assert_eq!(items[1].effect, Some(args::MessageEffect::Blink));
+  }
+
+  #[test]
+  fn load_playlist_skips_empty_text_before_the_first_header() {
+    let items = load("leading", "\n--- 2s\nHello\n").unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].text, "Hello");
+  }
+
+  #[test]
+  fn load_playlist_reports_the_line_of_invalid_headers() {
+    let error = load("invalid", "Hello\n--- 2s\nWorld\n--- volume=11\n").unwrap_err();
+    assert!(
+      error.ends_with(":4: unknown setting `volume`, expected one of duration, color, speed, effect"),
+      "{error}"
+    );
+    let error = load("color", "--- color=plaid\n").unwrap_err();
+    assert!(error.ends_with(":1: invalid color `plaid`"), "{error}");
+  }
+
+  #[test]
+  fn load_playlist_rejects_an_empty_file() {
+    assert!(load("empty", "").unwrap_err().ends_with("contains no messages"));
+  }
+}
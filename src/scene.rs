@@ -0,0 +1,344 @@
+use std::{fs, path::Path, time::Duration};
+
+use clap::ValueEnum;
+use ratatui::style::Color;
+use tui_rain_cli::{Animated, Easing, EffectRegistry, Interpolate, RainSpeed};
+
+use crate::args::{self, Args};
+
+/// A step of a scene, with the arguments it renders with.
+#[derive(Debug)]
+struct Step {
+  args: Args,
+  duration: Duration,
+  /// How long the transition from the previous step takes, at the start of this step.
+  fade: Duration,
+}
+
+/// A looping sequence of rain presets with transitions between them.
+#[derive(Debug)]
+pub struct Scene {
+  steps: Vec<Step>,
+  speed: Timeline<RainSpeed>,
+  /// The number of drops per cell, so fades blend the number of drops rather than the
+  /// space per drop.
+  drops: Timeline<f64>,
+  tail_lifespan: Timeline<Duration>,
+  color: Timeline<Color>,
+  head_color: Timeline<Color>,
+  message_color: Timeline<Color>,
+}
+
+/// A setting of a scene over time, fading linearly from each step to the next.
+///
+/// The first loop starts at the value of the first step. Every later loop fades into
+/// the first step from the last one.
+#[derive(Debug)]
+struct Timeline<T> {
+  first_loop: Animated<T>,
+  later_loops: Animated<T>,
+  loop_duration: Duration,
+}
+
+impl<T: Interpolate> Timeline<T> {
+  fn new(steps: &[Step], value: impl Fn(&Args) -> T) -> Timeline<T> {
+    let mut first_loop = Animated::new(value(&steps[0].args));
+    let mut later_loops = Animated::new(value(&steps[steps.len() - 1].args));
+    for (i, step) in steps.iter().enumerate() {
+      let value = value(&step.args);
+      let first_fade = if i == 0 { Duration::ZERO } else { step.fade };
+      first_loop = first_loop
+        .then(first_fade, value.clone(), Easing::Linear)
+        .then(step.duration - first_fade, value.clone(), Easing::Linear);
+      later_loops = later_loops
+        .then(step.fade, value.clone(), Easing::Linear)
+        .then(step.duration - step.fade, value, Easing::Linear);
+    }
+    Timeline {
+      first_loop,
+      later_loops: later_loops.with_repeat(true),
+      loop_duration: steps.iter().map(|step| step.duration).sum(),
+    }
+  }
+
+  /// Get the value at `elapsed`.
+  fn value_at(&self, elapsed: Duration) -> T {
+    match elapsed.checked_sub(self.loop_duration) {
+      Some(since_first_loop) => self.later_loops.value_at(since_first_loop),
+      None => self.first_loop.value_at(elapsed),
+    }
+  }
+
+  /// Integrate a numeric view of the value from the start of the scene to `elapsed`.
+  fn integrate(&self, elapsed: Duration, f: impl Fn(&T) -> f64) -> f64 {
+    match elapsed.checked_sub(self.loop_duration) {
+      Some(since_first_loop) => self.first_loop.integrate(self.loop_duration, &f) + self.later_loops.integrate(since_first_loop, &f),
+      None => self.first_loop.integrate(elapsed, &f),
+    }
+  }
+}
+
+/// What to render at a point of a scene.
+pub struct SceneFrame {
+  /// The arguments to build the rain with, interpolated during transitions.
+  pub args: Args,
+  /// The time to build the rain at. This follows the distance the drops have fallen
+  /// rather than the wall clock, so they don't jump when the speed changes. Templates
+  /// should keep using the wall clock.
+  pub drop_elapsed: Duration,
+  /// How long the current step has been shown.
+  pub step_elapsed: Duration,
+}
+
+/// Load a scene file.
+///
+/// Every line is a step made of a rain type, a duration and optional `key=value`
+/// settings, which override the command line arguments for that step. Values with
+/// spaces can be quoted, and lines starting with `#` are comments:
+///
+/// ```text
+/// # Office lobby loop
+/// snow 30s
+/// rain 20s fade=3s speed=4 message="Welcome to the office"
+/// matrix 30s fade=5s color=#00ff00
+/// ```
+///
+/// `fade` sets how long the transition from the previous step takes. Transitions fade
+/// the parameters rather than cross-fading two renders: speed, density, tail lifespan
+/// and colors are interpolated, while the rain type, glyphs, message and the other
+/// settings switch halfway through. The scene loops, fading from the last step back
+/// to the first.
+pub fn load_scene(path: &Path, base: &Args, effects: &EffectRegistry) -> Result<Scene, String> {
+  let content = fs::read_to_string(path).map_err(|e| format!("failed to read scene {}: {e}", path.display()))?;
+  let mut steps = vec![];
+  for (number, line) in content.lines().enumerate() {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+      continue;
+    }
+    let step = parse_step(line, base, effects).map_err(|e| format!("{}:{}: {e}", path.display(), number + 1))?;
+    steps.push(step);
+  }
+  if steps.iter().all(|step| step.duration.is_zero()) {
+    return Err(format!("scene {} contains no steps", path.display()));
+  }
+  Ok(Scene::new(steps))
+}
+
+/// Parse a step line into its arguments, starting from the command line arguments.
+fn parse_step(line: &str, base: &Args, effects: &EffectRegistry) -> Result<Step, String> {
+  let tokens = split_tokens(line)?;
+  let [rain_type, duration, settings @ ..] = &tokens[..] else {
+    return Err("expected a rain type and a duration".to_string());
+  };
+  let mut args = base.clone();
+  if effects.defaults(rain_type).is_none() {
+    let names: Vec<&str> = effects.names().collect();
+    return Err(format!("unknown rain type `{rain_type}`, expected one of {}", names.join(", ")));
+  }
+  args.rain_type = rain_type.clone();
+  let mut step = Step {
+    args,
+    duration: args::parse_duration(duration)?,
+    fade: Duration::ZERO,
+  };
+
+  let number = |value: &str| value.parse().map_err(|_| format!("invalid number `{value}`"));
+  let color = |value: &str| value.parse().map_err(|_| format!("invalid color `{value}`"));
+  for setting in settings {
+    let (key, value) = setting.split_once('=').ok_or_else(|| format!("expected `key=value`, found `{setting}`"))?;
+    let args = &mut step.args;
+    match key {
+      "fade" => step.fade = args::parse_duration(value)?,
+      "speed" => args.speed = Some(number(value)?),
+      "density" => args.density = Some(value.parse().map_err(|_| format!("invalid density `{value}`"))?),
+      "variance" => args.variance_speed = Some(number(value)?),
+      "tail" => args.lifespan_tail = Some(args::parse_duration(value)?.as_millis() as u64),
+      "color" => args.color = Some(color(value)?),
+      "head-color" => args.head_color = Some(color(value)?),
+      "message" => args.message = vec![value.to_string()],
+      "message-color" => args.message_color = Some(color(value)?),
+      "effect" => args.message_effect = args::MessageEffect::from_str(value, true)?,
+      key => {
+        return Err(format!(
+          "unknown setting `{key}`, expected one of fade, speed, density, variance, tail, color, head-color, message, message-color, effect"
+        ))
+      }
+    }
+  }
+  step.fade = step.fade.min(step.duration);
+  step.args.sanitize(effects);
+  Ok(step)
+}
+
+/// Split a line at whitespace, keeping double quoted parts together without the quotes.
+fn split_tokens(line: &str) -> Result<Vec<String>, String> {
+  let mut tokens = vec![];
+  let mut token = String::new();
+  let mut quoted = false;
+  for c in line.chars() {
+    match c {
+      '"' => quoted = !quoted,
+      c if c.is_whitespace() && !quoted => {
+        if !token.is_empty() {
+          tokens.push(std::mem::take(&mut token));
+        }
+      }
+      c => token.push(c),
+    }
+  }
+  if quoted {
+    return Err("unterminated quote".to_string());
+  }
+  if !token.is_empty() {
+    tokens.push(token);
+  }
+  Ok(tokens)
+}
+
+impl Scene {
+  /// Construct a scene from its steps, at least one of which takes time.
+  fn new(steps: Vec<Step>) -> Scene {
+    Scene {
+      speed: Timeline::new(&steps, |args| RainSpeed::Absolute { speed: args.speed.unwrap() }),
+      drops: Timeline::new(&steps, |args| 1.0 / args.density.unwrap().max(1) as f64),
+      tail_lifespan: Timeline::new(&steps, |args| Duration::from_millis(args.lifespan_tail.unwrap())),
+      color: Timeline::new(&steps, |args| args.color.unwrap()),
+      head_color: Timeline::new(&steps, |args| args.head_color.unwrap()),
+      message_color: Timeline::new(&steps, |args| args.message_color.unwrap()),
+      steps,
+    }
+  }
+
+  /// Find the step shown at `elapsed`, how long it has been shown for and how many
+  /// times the scene has looped before it.
+  fn position(&self, elapsed: Duration) -> (usize, Duration, u32) {
+    let total: Duration = self.steps.iter().map(|step| step.duration).sum();
+    let loops = (elapsed.as_nanos() / total.as_nanos()) as u32;
+    let mut offset = Duration::from_nanos((elapsed.as_nanos() % total.as_nanos()) as u64);
+    for (i, step) in self.steps.iter().enumerate() {
+      if offset < step.duration {
+        return (i, offset, loops);
+      }
+      offset -= step.duration;
+    }
+    (self.steps.len() - 1, offset, loops)
+  }
+
+  /// The step a step fades in from, or `None` if it doesn't fade, like the very first
+  /// step when the scene starts.
+  fn fade_from(&self, i: usize, first_loop: bool) -> Option<&Step> {
+    if self.steps[i].fade.is_zero() || (i == 0 && first_loop) {
+      return None;
+    }
+    Some(&self.steps[(i + self.steps.len() - 1) % self.steps.len()])
+  }
+
+  /// Compute what to render at `elapsed`.
+  pub fn frame(&self, elapsed: Duration) -> SceneFrame {
+    let (i, offset, loops) = self.position(elapsed);
+    let step = &self.steps[i];
+
+    // Settings that can't be blended switch halfway through the fade.
+    let mut args = match self.fade_from(i, loops == 0) {
+      Some(from) if offset < step.fade / 2 => Args {
+        message: step.args.message.clone(),
+        ..from.args.clone()
+      },
+      _ => step.args.clone(),
+    };
+    let speed = self.speed.value_at(elapsed).speed();
+    args.speed = Some(speed);
+    args.density = Some((1.0 / self.drops.value_at(elapsed)).round() as usize);
+    args.lifespan_tail = Some(self.tail_lifespan.value_at(elapsed).as_millis() as u64);
+    args.color = Some(self.color.value_at(elapsed));
+    args.head_color = Some(self.head_color.value_at(elapsed));
+    args.message_color = Some(self.message_color.value_at(elapsed));
+
+    let distance = self.speed.integrate(elapsed, RainSpeed::speed);
+    SceneFrame {
+      args,
+      drop_elapsed: Duration::try_from_secs_f64(distance / speed.max(1e-3)).unwrap_or_default(),
+      step_elapsed: offset,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use clap::Parser;
+
+  use super::*;
+
+  fn base() -> (Args, EffectRegistry) {
+    (Args::parse_from(["tui-rain-cli"]), args::effects())
+  }
+
+  #[test]
+  fn frame_follows_the_distance_fallen() {
+    let (base, effects) = base();
+    let steps = ["rain 10s fade=2s speed=2", "snow 10s fade=4s speed=6"];
+    let scene = Scene::new(steps.iter().map(|line| parse_step(line, &base, &effects).unwrap()).collect());
+    let frame = |secs| scene.frame(Duration::from_secs(secs));
+
+    // The very first step doesn't fade in.
+    assert_eq!(frame(5).args.speed, Some(2.0));
+    assert_eq!(frame(5).drop_elapsed, Duration::from_secs(5));
+
+    // Halfway through the fade, the speed is halfway and the rain has fallen 20 cells
+    // during the first step and 4 + 2 during the fade.
+    let fading = frame(12);
+    assert_eq!((fading.args.rain_type.as_str(), fading.args.speed), ("snow", Some(4.0)));
+    assert_eq!(fading.drop_elapsed, Duration::from_secs_f64(26.0 / 4.0));
+    assert_eq!(fading.step_elapsed, Duration::from_secs(2));
+    assert_eq!(frame(11).args.rain_type, "rain");
+
+    // Later loops fade into the first step, after falling 20 + 16 + 36 cells in the first.
+    let looped = frame(21);
+    assert_eq!(looped.args.speed, Some(4.0));
+    assert_eq!(looped.drop_elapsed, Duration::from_secs_f64(77.0 / 4.0));
+  }
+
+  #[test]
+  fn split_tokens_keeps_quoted_values_together() {
+    assert_eq!(
+      split_tokens(r#"  rain  20s fade=3s message="Welcome to  the office" "#).unwrap(),
+      ["rain", "20s", "fade=3s", "message=Welcome to  the office"]
+    );
+    assert_eq!(split_tokens("").unwrap(), Vec::<String>::new());
+    assert_eq!(split_tokens(r#"rain 1s message="oops"#).unwrap_err(), "unterminated quote");
+  }
+
+  #[test]
+  fn parse_step_overrides_the_command_line_arguments() {
+    let (base, effects) = base();
+    let step = parse_step(r#"matrix 30s fade=5s speed=4 color=#00ff00 message="Hi there" effect=decode"#, &base, &effects).unwrap();
+    assert_eq!(step.args.rain_type, "matrix");
+    assert_eq!(step.duration, Duration::from_secs(30));
+    assert_eq!(step.fade, Duration::from_secs(5));
+    assert_eq!(step.args.speed, Some(4.0));
+    assert_eq!(step.args.color, Some(Color::Rgb(0, 255, 0)));
+    assert_eq!(step.args.message, ["Hi there"]);
+    assert_eq!(step.args.message_effect, args::MessageEffect::Decode);
+    // Settings that weren't given come from the preset.
+    assert_eq!(step.args.head_color, effects.defaults("matrix").map(|defaults| defaults.head_color));
+  }
+
+  #[test]
+  fn parse_step_limits_the_fade_to_the_step() {
+    let (base, effects) = base();
+    assert_eq!(parse_step("snow 2s fade=5s", &base, &effects).unwrap().fade, Duration::from_secs(2));
+  }
+
+  #[test]
+  fn parse_step_rejects_invalid_steps() {
+    let (base, effects) = base();
+    let error = |line| parse_step(line, &base, &effects).unwrap_err();
+    assert_eq!(error("rain"), "expected a rain type and a duration");
+    assert!(error("drizzle 5s").starts_with("unknown rain type `drizzle`, expected one of rain, "));
+    assert_eq!(error("rain 5s speed"), "expected `key=value`, found `speed`");
+    assert_eq!(error("rain 5s speed=fast"), "invalid number `fast`");
+    assert_eq!(error("rain 5s color=plaid"), "invalid color `plaid`");
+    assert!(error("rain 5s volume=11").starts_with("unknown setting `volume`"));
+  }
+}
@@ -0,0 +1,327 @@
+use std::time::Duration;
+
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use ratatui::{
+  buffer::Buffer,
+  layout::Rect,
+  style::{Color, Style, Stylize},
+  widgets::Widget,
+};
+
+use crate::{Effect, EffectConfig, RainDensity, RainSpeed};
+
+/// How close stars get before they pass the viewer, as a fraction of the depth of the
+/// field. Stars at this depth are drawn at their full distance from the center.
+const NEAR_PLANE: f64 = 0.15;
+
+/// How far from the center stars are placed, relative to the edges of the screen.
+const SPREAD: f64 = 1.5;
+
+/// A starfield widget.
+///
+/// Stars stream outward from the center of the screen as they approach, growing from
+/// `.` to `+` to `*` and getting brighter. In warp mode, they stretch into streaks. Like
+/// [`Rain`](crate::Rain), everything is computed from the elapsed time, so no state is
+/// kept between frames.
+///
+/// ```
+/// use std::time::Duration;
+/// use tui_rain_cli::Starfield;
+///
+/// let elapsed = Duration::from_secs(5);
+///
+/// Starfield::new(elapsed);
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct Starfield {
+  elapsed: Duration,
+  seed: u64,
+  star_density: RainDensity,
+  star_speed: RainSpeed,
+  speed_variance: f64,
+  color: Color,
+  head_color: Color,
+  warp: bool,
+  tail_lifespan: Duration,
+  bold_dim_effect: bool,
+}
+
+impl Starfield {
+  /// Construct a new starfield widget with defaults.
+  pub fn new(elapsed: Duration) -> Starfield {
+    Starfield {
+      elapsed,
+      seed: 1234,
+      star_density: RainDensity::Relative { sparseness: 20 },
+      star_speed: RainSpeed::Absolute { speed: 20.0 },
+      speed_variance: 0.3,
+      color: Color::White,
+      head_color: Color::White,
+      warp: false,
+      tail_lifespan: Duration::from_millis(300),
+      bold_dim_effect: true,
+    }
+  }
+
+  /// Set the random seed for the stars.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::Starfield;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Starfield::new(elapsed)
+  ///     .with_seed(2027);
+  /// ```
+  pub fn with_seed(mut self, seed: u64) -> Starfield {
+    self.seed = seed;
+    self
+  }
+
+  /// Set how many stars there are, computed like the number of drops of
+  /// [rain](crate::Rain::with_rain_density).
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{RainDensity, Starfield};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Starfield::new(elapsed)
+  ///     .with_star_density(RainDensity::Absolute { num_drops: 200 });
+  /// ```
+  pub fn with_star_density(mut self, star_density: RainDensity) -> Starfield {
+    self.star_density = star_density;
+    self
+  }
+
+  /// Set how fast stars approach, in cells / second of depth. The field is as deep as
+  /// the screen is wide or tall, whichever is larger.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{RainSpeed, Starfield};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Starfield::new(elapsed)
+  ///     .with_star_speed(RainSpeed::Absolute { speed: 40.0 });
+  /// ```
+  pub fn with_star_speed(mut self, star_speed: RainSpeed) -> Starfield {
+    self.star_speed = star_speed;
+    self
+  }
+
+  /// Set how much the speed of each star varies, as a fraction of the star speed.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::Starfield;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Starfield::new(elapsed)
+  ///     .with_speed_variance(0.5);
+  /// ```
+  pub fn with_speed_variance(mut self, speed_variance: f64) -> Starfield {
+    self.speed_variance = speed_variance;
+    self
+  }
+
+  /// Set the color of the stars.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use ratatui::style::Color;
+  /// use tui_rain_cli::Starfield;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Starfield::new(elapsed)
+  ///     .with_color(Color::LightBlue);
+  /// ```
+  pub fn with_color(mut self, color: Color) -> Starfield {
+    self.color = color;
+    self
+  }
+
+  /// Set the color of the nearest stars, drawn as `*`.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use ratatui::style::Color;
+  /// use tui_rain_cli::Starfield;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Starfield::new(elapsed)
+  ///     .with_head_color(Color::LightYellow);
+  /// ```
+  pub fn with_head_color(mut self, head_color: Color) -> Starfield {
+    self.head_color = head_color;
+    self
+  }
+
+  /// Set whether stars stretch into streaks, as if travelling at warp speed.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::Starfield;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Starfield::new(elapsed)
+  ///     .with_warp(true);
+  /// ```
+  pub fn with_warp(mut self, warp: bool) -> Starfield {
+    self.warp = warp;
+    self
+  }
+
+  /// Set how long the path is that streaks cover in warp mode.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::Starfield;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Starfield::new(elapsed)
+  ///     .with_warp(true)
+  ///     .with_tail_lifespan(Duration::from_millis(500));
+  /// ```
+  pub fn with_tail_lifespan(mut self, tail_lifespan: Duration) -> Starfield {
+    self.tail_lifespan = tail_lifespan;
+    self
+  }
+
+  /// Set whether stars get brighter with bold and dim as they approach.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::Starfield;
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Starfield::new(elapsed)
+  ///     .with_bold_dim_effect(false);
+  /// ```
+  pub fn with_bold_dim_effect(mut self, bold_dim_effect: bool) -> Starfield {
+    self.bold_dim_effect = bold_dim_effect;
+    self
+  }
+
+  /// Apply the settings shared by all [effects](Effect).
+  ///
+  /// The density sets the number of stars, the speed how fast they approach, and the
+  /// tail lifespan the length of warp streaks.
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use tui_rain_cli::{EffectConfig, Starfield};
+  ///
+  /// let elapsed = Duration::from_secs(5);
+  ///
+  /// Starfield::new(elapsed)
+  ///     .with_config(&EffectConfig::default());
+  /// ```
+  pub fn with_config(self, config: &EffectConfig) -> Starfield {
+    self
+      .with_seed(config.seed)
+      .with_star_density(config.density)
+      .with_star_speed(config.speed)
+      .with_speed_variance(config.speed_variance)
+      .with_tail_lifespan(config.tail_lifespan)
+      .with_color(config.color)
+      .with_head_color(config.head_color)
+      .with_bold_dim_effect(config.bold_dim_effect)
+  }
+
+  /// The glyph and style of a star at a depth, from 0 (passing the viewer) to 1 (the
+  /// back of the field).
+  fn star_look(&self, depth: f64) -> (char, Style) {
+    let (content, color) = match depth {
+      depth if depth > 0.6 => ('.', self.color),
+      depth if depth > 0.3 => ('+', self.color),
+      _ => ('*', self.head_color),
+    };
+    let mut style = Style::default().fg(color);
+    if self.bold_dim_effect {
+      style = match depth {
+        depth if depth > 0.6 => style.dim(),
+        depth if depth > 0.3 => style,
+        _ => style.bold(),
+      };
+    }
+    (content, style)
+  }
+}
+
+impl Widget for Starfield {
+  fn render(self, area: Rect, buf: &mut Buffer) {
+    if area.is_empty() {
+      return;
+    }
+    let elapsed = self.elapsed.as_secs_f64();
+    let (width, height) = (area.width as f64, area.height as f64);
+    let (center_x, center_y) = (width / 2.0, height / 2.0);
+    let field_depth = width.max(height);
+    let near = field_depth * NEAR_PLANE;
+    let variance = self.speed_variance.clamp(0.0, 0.9);
+    let mut set = |x: f64, y: f64, content: char, style: Style| {
+      let (x, y) = (x.floor(), y.floor());
+      if x >= 0.0 && y >= 0.0 && x < width && y < height {
+        buf[(area.x + x as u16, area.y + y as u16)].set_char(content).set_style(style);
+      }
+    };
+
+    for star in 0..self.star_density.num_drops(area) as u64 {
+      let star_seed = self.seed ^ star.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+      let speed = self.star_speed.speed() * crate::uniform(star_seed, 1.0 - variance, 1.0 + variance);
+      let cycle_time = (field_depth - near) / speed.max(1e-3);
+      let phase = crate::uniform(star_seed.rotate_left(32), 0.0, cycle_time);
+      let cycle = ((elapsed + phase) / cycle_time) as u64;
+      let since_spawn = (elapsed + phase) - cycle as f64 * cycle_time;
+
+      // Every cycle, the star respawns at the back of the field at a new position.
+      let mut rng = Pcg64Mcg::seed_from_u64(star_seed ^ cycle.wrapping_mul(0xc2b2_ae3d_27d4_eb4f));
+      // Spread stars beyond the screen, so some of them leave it before passing the viewer.
+      let offset_x = rng.gen_range(-center_x..=center_x) * SPREAD;
+      let offset_y = rng.gen_range(-center_y..=center_y) * SPREAD;
+      let project = |t: f64| {
+        let z = (field_depth - speed * t).max(near);
+        (center_x + offset_x * near / z, center_y + offset_y * near / z, z)
+      };
+
+      let (x, y, z) = project(since_spawn);
+      let depth = (z - near) / (field_depth - near);
+      let (content, style) = self.star_look(depth);
+      if self.warp {
+        // Streak over the path the star covered during the tail lifespan.
+        let (tail_x, tail_y, _) = project((since_spawn - self.tail_lifespan.as_secs_f64()).max(0.0));
+        let (dx, dy) = (x - tail_x, y - tail_y);
+        // Cells are about twice as tall as they are wide.
+        let streak = match (dy * 2.0).atan2(dx).to_degrees().rem_euclid(180.0) {
+          angle if !(22.5..157.5).contains(&angle) => '-',
+          angle if angle < 67.5 => '\\',
+          angle if angle < 112.5 => '|',
+          _ => '/',
+        };
+        let steps = dx.abs().max(dy.abs()).ceil() as usize;
+        for step in 0..steps {
+          let t = step as f64 / steps as f64;
+          set(tail_x + dx * t, tail_y + dy * t, streak, style.not_bold());
+        }
+      }
+      set(x, y, content, style);
+    }
+  }
+}
+
+impl Effect for Starfield {
+  fn render_at(&self, elapsed: Duration, area: Rect, buf: &mut Buffer) {
+    Starfield { elapsed, ..self.clone() }.render(area, buf);
+  }
+}
@@ -0,0 +1,173 @@
+use std::{fmt::Write, fs, time::Duration};
+
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone};
+
+/// Values that stay constant while rendering, resolved once at startup.
+#[derive(Debug)]
+pub struct TemplateContext {
+  hostname: String,
+  user: String,
+  boot_uptime: Option<Duration>,
+}
+
+impl TemplateContext {
+  pub fn new() -> TemplateContext {
+    TemplateContext {
+      hostname: gethostname::gethostname().to_string_lossy().into_owned(),
+      user: std::env::var("USER").or_else(|_| std::env::var("USERNAME")).unwrap_or_default(),
+      boot_uptime: system_uptime(),
+    }
+  }
+
+  /// Expand the placeholders in a message template.
+  ///
+  /// Supported placeholders are `{time}`, `{time:<format>}`, `{date}`, `{date:<format>}`,
+  /// `{hostname}`, `{user}`, `{uptime}`, `{countdown:<YYYY-MM-DDTHH:MM>}` and `{fps}`,
+  /// where formats use strftime syntax. `{uptime}` is the system uptime where available,
+  /// and otherwise how long the rain has been running. `{{` and `}}` produce literal braces, and
+  /// unknown placeholders are kept as is.
+  pub fn render(&self, template: &str, elapsed: Duration, fps: f64) -> String {
+    if !template.contains(['{', '}']) {
+      return template.to_string();
+    }
+
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+      output.push_str(&rest[..start]);
+      rest = &rest[start..];
+      if rest.starts_with("{{") || rest.starts_with("}}") {
+        output.push_str(&rest[..1]);
+        rest = &rest[2..];
+        continue;
+      }
+      let expanded = rest.strip_prefix('{').and_then(|inner| {
+        let end = inner.find('}')?;
+        Some((self.expand(&inner[..end], elapsed, fps)?, end + 2))
+      });
+      match expanded {
+        Some((value, len)) => {
+          output.push_str(&value);
+          rest = &rest[len..];
+        }
+        None => {
+          output.push_str(&rest[..1]);
+          rest = &rest[1..];
+        }
+      }
+    }
+    output.push_str(rest);
+    output
+  }
+
+  /// Expand a single placeholder, without braces. Returns `None` if it isn't known.
+  fn expand(&self, placeholder: &str, elapsed: Duration, fps: f64) -> Option<String> {
+    let (name, argument) = match placeholder.split_once(':') {
+      Some((name, argument)) => (name, Some(argument)),
+      None => (placeholder, None),
+    };
+    match (name, argument) {
+      ("time", format) => format_now(format.unwrap_or("%H:%M:%S")),
+      ("date", format) => format_now(format.unwrap_or("%Y-%m-%d")),
+      ("hostname", None) => Some(self.hostname.clone()),
+      ("user", None) => Some(self.user.clone()),
+      ("uptime", None) => Some(format_duration(self.boot_uptime.unwrap_or_default() + elapsed)),
+      ("countdown", Some(target)) => countdown(target),
+      ("fps", None) => Some(format!("{}", fps.round())),
+      _ => None,
+    }
+  }
+}
+
+/// Format the current local time, or `None` if the format is invalid.
+fn format_now(format: &str) -> Option<String> {
+  let mut output = String::new();
+  write!(output, "{}", Local::now().format(format)).ok()?;
+  Some(output)
+}
+
+/// Format the time left until a local date (and optional time) as `[Nd ]HH:MM:SS`.
+fn countdown(target: &str) -> Option<String> {
+  let target = NaiveDateTime::parse_from_str(target, "%Y-%m-%dT%H:%M")
+    .or_else(|_| NaiveDateTime::parse_from_str(target, "%Y-%m-%dT%H:%M:%S"))
+    .or_else(|_| NaiveDate::parse_from_str(target, "%Y-%m-%d").map(|date| date.and_time(Default::default())))
+    .ok()?;
+  let target = Local.from_local_datetime(&target).earliest()?;
+  let remaining = (target - Local::now()).to_std().unwrap_or_default().as_secs();
+  let (days, hours, minutes, seconds) = (remaining / 86400, remaining / 3600 % 24, remaining / 60 % 60, remaining % 60);
+  if days > 0 {
+    Some(format!("{days}d {hours:02}:{minutes:02}:{seconds:02}"))
+  } else {
+    Some(format!("{hours:02}:{minutes:02}:{seconds:02}"))
+  }
+}
+
+/// Format a duration coarsely, e.g. `3d 4h 12m` or `12m 3s`.
+fn format_duration(duration: Duration) -> String {
+  let secs = duration.as_secs();
+  let (days, hours, minutes, seconds) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+  if days > 0 {
+    format!("{days}d {hours}h {minutes}m")
+  } else if hours > 0 {
+    format!("{hours}h {minutes}m")
+  } else {
+    format!("{minutes}m {seconds}s")
+  }
+}
+
+/// Read how long the system has been up, where the platform exposes it.
+fn system_uptime() -> Option<Duration> {
+  let uptime = fs::read_to_string("/proc/uptime").ok()?;
+  let secs: f64 = uptime.split_whitespace().next()?.parse().ok()?;
+  Duration::try_from_secs_f64(secs).ok()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn context() -> TemplateContext {
+    TemplateContext {
+      hostname: "rainbox".to_string(),
+      user: "ada".to_string(),
+      boot_uptime: None,
+    }
+  }
+
+  #[test]
+  fn render_expands_placeholders() {
+    let rendered = context().render("{user}@{hostname} up {uptime} at {fps} fps", Duration::from_secs(75), 59.6);
+    assert_eq!(rendered, "ada@rainbox up 1m 15s at 60 fps");
+  }
+
+  #[test]
+  fn render_keeps_escaped_braces_and_unknown_placeholders() {
+    let context = context();
+    assert_eq!(context.render("{{user}} is {user}", Duration::ZERO, 0.0), "{user} is ada");
+    assert_eq!(context.render("{nope} and {user", Duration::ZERO, 0.0), "{nope} and {user");
+    assert_eq!(context.render("a } b", Duration::ZERO, 0.0), "a } b");
+  }
+
+  #[test]
+  fn render_adds_elapsed_time_to_the_system_uptime() {
+    let context = TemplateContext {
+      boot_uptime: Some(Duration::from_secs(2 * 86400 + 3 * 3600)),
+      ..context()
+    };
+    assert_eq!(context.render("{uptime}", Duration::from_secs(600), 0.0), "2d 3h 10m");
+  }
+
+  #[test]
+  fn render_counts_down_to_zero_once_the_target_has_passed() {
+    let context = context();
+    assert_eq!(context.render("{countdown:2000-01-01T00:00}", Duration::ZERO, 0.0), "00:00:00");
+    assert_eq!(context.render("{countdown:2000-01-01}", Duration::ZERO, 0.0), "00:00:00");
+    assert_eq!(context.render("{countdown:soon}", Duration::ZERO, 0.0), "{countdown:soon}");
+  }
+
+  #[test]
+  fn format_duration_is_coarse() {
+    assert_eq!(format_duration(Duration::from_secs(59)), "0m 59s");
+    assert_eq!(format_duration(Duration::from_secs(3 * 3600 + 61)), "3h 1m");
+  }
+}
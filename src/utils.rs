@@ -1,28 +1,120 @@
-use std::error::Error;
+use std::{
+  error::Error,
+  io::{BufRead, Write},
+  sync::{Arc, Mutex},
+  thread,
+};
 
-use crossterm::event::{Event, EventStream, KeyCode};
+use crossterm::{
+  event::{Event, EventStream, KeyCode},
+  terminal,
+};
 use futures::{FutureExt, StreamExt};
-use ratatui::{style::Stylize, DefaultTerminal, Frame};
+use ratatui::{
+  layout::Rect,
+  style::{Color, Stylize},
+  DefaultTerminal, Frame,
+};
 use tokio::time;
-use tui_rain_cli::Rain;
+use tui_rain_cli::{Canvas, Capabilities, CharacterSet, Effect, GraphicsProtocol};
+
+/// The layers of a frame, drawn bottom to top, each with the time to render it at.
+pub type Layers = Vec<(time::Duration, Box<dyn Effect>)>;
 
 /// How much to smooth the FPS tracking.
 ///
 /// Values closer to 1 are smoother, values closer to 0 are more responsive.
 const FPS_SMOOTHING: f64 = 0.95;
 
-/// A helper function to manage rendering a rain widget and an FPS tracker.
+/// How many characters read from stdin are kept to feed the drops.
+const STDIN_BUFFER_LEN: usize = 4096;
+
+/// The most recent characters read from stdin, stored as a fixed-size ring.
 ///
-/// Given a callback to construct a rain widget given an elapsed duration.
-pub async fn render_rain(rain: Box<dyn Fn(time::Duration) -> Rain>) -> Result<(), Box<dyn Error>> {
+/// Once full, new characters overwrite the oldest slot, and `head` points at the
+/// oldest character left.
+#[derive(Default, Debug)]
+pub struct GlyphRing {
+  chars: Vec<char>,
+  head: usize,
+  /// The characters in arrival order, rebuilt only after new input arrives.
+  ordered: Option<Arc<[char]>>,
+}
+
+impl GlyphRing {
+  fn push(&mut self, c: char) {
+    if self.chars.len() < STDIN_BUFFER_LEN {
+      self.chars.push(c);
+    } else {
+      self.chars[self.head] = c;
+      self.head = (self.head + 1) % STDIN_BUFFER_LEN;
+    }
+    self.ordered = None;
+  }
+
+  /// Get the character set to render, oldest character first, or `None` if nothing
+  /// has been read yet.
+  pub fn character_set(&mut self) -> Option<CharacterSet> {
+    if self.chars.is_empty() {
+      return None;
+    }
+    let (newest, oldest) = self.chars.split_at(self.head);
+    let chars = self.ordered.get_or_insert_with(|| oldest.iter().chain(newest).copied().collect());
+    Some(CharacterSet::Sequence { chars: chars.clone() })
+  }
+}
+
+/// Spawn a reader that feeds stdin line by line into a shared [`GlyphRing`].
+///
+/// This uses a plain thread rather than `tokio::io::stdin`, since a pending tokio read
+/// keeps the runtime from shutting down until the pipe produces more data. Keyboard
+/// events keep working while stdin is a pipe, as crossterm reads them from `/dev/tty`
+/// whenever stdin is not a terminal.
+pub fn spawn_stdin_reader() -> Arc<Mutex<GlyphRing>> {
+  let ring = Arc::new(Mutex::new(GlyphRing::default()));
+  let writer = ring.clone();
+  thread::spawn(move || {
+    for line in std::io::stdin().lock().split(b'\n') {
+      let Ok(line) = line else { break };
+      let mut ring = writer.lock().unwrap();
+      String::from_utf8_lossy(&line).chars().filter(|c| !c.is_control()).for_each(|c| ring.push(c));
+      // Keep consecutive lines from running into each other.
+      ring.push(' ');
+    }
+  });
+  ring
+}
+
+/// A helper function to manage rendering effects and an FPS tracker.
+///
+/// Given a callback to construct the layers of effects given an elapsed duration and
+/// the current smoothed FPS.
+///
+/// With a graphics protocol, drops are drawn in pixels where the terminal allows it.
+/// Each frame is degraded to the capabilities of the terminal once all layers are drawn.
+pub async fn render_rain(
+  rain: Box<dyn Fn(time::Duration, f64) -> Layers>,
+  graphics: Option<GraphicsProtocol>,
+  capabilities: Capabilities,
+) -> Result<(), Box<dyn Error>> {
   let mut terminal = ratatui::init();
   terminal.clear().unwrap();
-  let result = main_loop(rain, terminal, 60.0).await;
+  let result = main_loop(rain, &mut terminal, 60.0, graphics, capabilities).await;
+  if let Some(graphics) = graphics {
+    terminal.backend_mut().write_all(graphics.clear())?;
+    terminal.backend_mut().flush()?;
+  }
   ratatui::restore();
   result
 }
 
-async fn main_loop(rain: Box<dyn Fn(time::Duration) -> Rain>, mut terminal: DefaultTerminal, framerate: f64) -> Result<(), Box<dyn Error>> {
+async fn main_loop(
+  rain: Box<dyn Fn(time::Duration, f64) -> Layers>,
+  terminal: &mut DefaultTerminal,
+  framerate: f64,
+  graphics: Option<GraphicsProtocol>,
+  capabilities: Capabilities,
+) -> Result<(), Box<dyn Error>> {
   // Read terminal events
   let mut reader = EventStream::new();
 
@@ -38,6 +130,10 @@ async fn main_loop(rain: Box<dyn Fn(time::Duration) -> Rain>, mut terminal: Defa
   let mut last_tick = time::Instant::now().checked_sub(tick_duration).unwrap();
   let mut fps: f64 = framerate;
 
+  // Keep the canvas between frames, and the last image drawn to skip unchanged ones.
+  let mut pixels = graphics.map(Pixels::new);
+  let mut last_image = Vec::new();
+
   loop {
     // Wait for next tick or term signal
     tokio::select! {
@@ -47,8 +143,19 @@ async fn main_loop(rain: Box<dyn Fn(time::Duration) -> Rain>, mut terminal: Defa
             last_tick = time::Instant::now();
             fps = fps.min(1e4) * FPS_SMOOTHING + (1.0 - FPS_SMOOTHING) / elapsed.as_secs_f64();
 
-            // Render
-            terminal.draw(|frame| render(&rain, frame, start_time.elapsed(), fps, show_fps))?;
+            // Render, and then draw the image over the cells
+            let mut image = None;
+            terminal.draw(|frame| {
+                image = render(rain.as_ref(), frame, start_time.elapsed(), fps, show_fps, pixels.as_mut(), capabilities)
+            })?;
+            if let Some(image) = image.filter(|image| *image != last_image) {
+                let backend = terminal.backend_mut();
+                backend.write_all(b"\x1b7\x1b[1;1H")?;
+                backend.write_all(&image)?;
+                backend.write_all(b"\x1b8")?;
+                backend.flush()?;
+                last_image = image;
+            }
         },
 
         event = reader.next().fuse() => match event {
@@ -66,14 +173,93 @@ async fn main_loop(rain: Box<dyn Fn(time::Duration) -> Rain>, mut terminal: Defa
   }
 }
 
-fn render(rain: &Box<dyn Fn(time::Duration) -> Rain>, frame: &mut Frame, elapsed: time::Duration, fps: f64, show_fps: bool) {
-  // Render the rain
-  frame.render_widget(rain(elapsed), frame.area());
+/// Render the frame, returning the image to draw over it with the graphics protocol.
+fn render(
+  rain: &dyn Fn(time::Duration, f64) -> Layers,
+  frame: &mut Frame,
+  elapsed: time::Duration,
+  fps: f64,
+  show_fps: bool,
+  pixels: Option<&mut Pixels>,
+  capabilities: Capabilities,
+) -> Option<Vec<u8>> {
+  // Render the effects
+  let area = frame.area();
+  let graphics = pixels.as_ref().map(|pixels| pixels.graphics);
+  let mut canvas = pixels.and_then(|pixels| pixels.canvas(area));
+  for (elapsed, effect) in rain(elapsed, fps) {
+    match canvas.as_mut() {
+      Some(canvas) => effect.render_pixels_at(elapsed, area, frame.buffer_mut(), canvas),
+      None => effect.render_at(elapsed, area, frame.buffer_mut()),
+    }
+  }
+  capabilities.degrade(area, frame.buffer_mut());
 
   // Render the FPS tracker
   if show_fps {
     frame.render_widget(format!("(f) FPS: {}", fps.round()).white().on_blue().not_bold().not_dim(), frame.area());
   }
+
+  let (graphics, canvas) = graphics.zip(canvas)?;
+  // Sixel images cover the cells, so leave holes for whatever was drawn in them.
+  if graphics == GraphicsProtocol::Sixel {
+    for position in area.positions() {
+      let cell = &frame.buffer_mut()[position];
+      if cell.symbol() != " " || cell.bg != Color::Reset {
+        canvas.clear_cell(position.x, position.y);
+      }
+    }
+  }
+  Some(graphics.encode(canvas))
+}
+
+/// Drawing in pixels with a graphics protocol, on a canvas kept between frames.
+struct Pixels {
+  graphics: GraphicsProtocol,
+  /// The canvas and the area it was created for.
+  canvas: Option<(Rect, Canvas)>,
+}
+
+impl Pixels {
+  fn new(graphics: GraphicsProtocol) -> Pixels {
+    Pixels { graphics, canvas: None }
+  }
+
+  /// Wipe the canvas of the previous frame to draw the next one on it, creating a new
+  /// one first if there is none yet or the area changed.
+  fn canvas(&mut self, area: Rect) -> Option<&mut Canvas> {
+    if self.canvas.as_ref().is_none_or(|(canvas_area, _)| *canvas_area != area) {
+      self.canvas = new_canvas(self.graphics, area).map(|canvas| (area, canvas));
+    }
+    let (_, canvas) = self.canvas.as_mut()?;
+    match self.graphics {
+      // Sixel images are painted black, so drops of previous frames are erased.
+      GraphicsProtocol::Sixel => canvas.fill([0, 0, 0, 255]),
+      // Kitty images replace the previous one, so the rest stays transparent.
+      GraphicsProtocol::Kitty => canvas.fill([0; 4]),
+    }
+    Some(canvas)
+  }
+}
+
+/// Create a canvas over the area for the size of the terminal's cells in pixels, or
+/// `None` to draw in cells if it's unknown.
+fn new_canvas(graphics: GraphicsProtocol, area: Rect) -> Option<Canvas> {
+  let cell = terminal::window_size()
+    .ok()
+    .filter(|size| size.columns > 0 && size.rows > 0)
+    .map(|size| (size.width / size.columns, size.height / size.rows))
+    .filter(|&(width, height)| width > 0 && height > 0);
+  match (graphics, cell) {
+    // Sixel images that reach the last row scroll the screen, so it stays in cells.
+    (GraphicsProtocol::Sixel, Some((width, height))) => Some(Canvas::new(area.width, area.height.saturating_sub(1), width, height)),
+    (GraphicsProtocol::Sixel, None) => None,
+    // Kitty scales images to the cells they cover, so any size will do.
+    (GraphicsProtocol::Kitty, cell) => {
+      let (width, height) = cell.unwrap_or((8, 16));
+      Some(Canvas::new(area.width, area.height, width, height))
+    }
+  }
 }
 
 #[allow(dead_code)]
@@ -82,3 +268,41 @@ async fn main() -> Result<(), Box<dyn Error>> {
   println!("this is a collection of utils and not independently executable");
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// The characters of a ring's sequence, as a string.
+  fn sequence(ring: &mut GlyphRing) -> String {
+    match ring.character_set() {
+      Some(CharacterSet::Sequence { chars }) => chars.iter().collect(),
+      other => panic!("expected a sequence, got {other:?}"),
+    }
+  }
+
+  #[test]
+  fn glyph_ring_is_empty_until_input_arrives() {
+    assert_eq!(GlyphRing::default().character_set(), None);
+  }
+
+  #[test]
+  fn glyph_ring_keeps_arrival_order_before_wrapping() {
+    let mut ring = GlyphRing::default();
+    "abc".chars().for_each(|c| ring.push(c));
+    assert_eq!(sequence(&mut ring), "abc");
+    ring.push('d');
+    assert_eq!(sequence(&mut ring), "abcd");
+  }
+
+  #[test]
+  fn glyph_ring_keeps_arrival_order_after_wrapping() {
+    let digit = |i: usize| char::from_digit((i % 10) as u32, 10).unwrap();
+    let mut ring = GlyphRing::default();
+    (0..STDIN_BUFFER_LEN + 3).for_each(|i| ring.push(digit(i)));
+
+    // The three oldest characters were overwritten, and the rest follow in order.
+    let expected: String = (3..STDIN_BUFFER_LEN + 3).map(digit).collect();
+    assert_eq!(sequence(&mut ring), expected);
+  }
+}